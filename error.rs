@@ -0,0 +1,173 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The error type for profile creation, sandbox startup, and sandbox activation.
+
+use profile::{Operation, ViolationAction};
+
+use libc::{self, c_int};
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Everything that can go wrong while creating a profile or starting or activating a sandbox.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// This operation cannot be allowed precisely on this platform, or is always allowed on it.
+    UnsupportedOperation(Operation),
+    /// This operation is malformed, such as a network whose prefix length is longer than its
+    /// addresses.
+    InvalidOperation(Operation),
+    /// This violation action cannot be taken, such as an `errno` value above 4095.
+    InvalidViolationAction(ViolationAction),
+    /// The patterns of these two operations overlap in a way that profiles may not contain.
+    OverlappingPatterns(Operation, Operation),
+    /// A system call failed.
+    Os {
+        /// What the sandbox was doing at the time.
+        stage: Stage,
+        /// The path involved, if any.
+        path: Option<PathBuf>,
+        /// The `errno` value that the system call failed with.
+        errno: c_int,
+    },
+    /// The operating system's sandboxing facility reported an error.
+    Platform {
+        /// What the sandbox was doing at the time.
+        stage: Stage,
+        /// The message reported by the operating system.
+        message: String,
+    },
+}
+
+/// The steps of starting and activating a sandbox, for reporting which one failed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Stage {
+    /// Spawning the sandboxed process.
+    Spawn,
+    /// Making every mount in the sandbox's mount namespace private, so that mount events don't
+    /// propagate into or out of it.
+    MakeMountsPrivate,
+    /// Creating the temporary directory that becomes the root of the `chroot` jail.
+    CreateJailDirectory,
+    /// Mounting the `tmpfs` that backs the `chroot` jail.
+    MountJailFilesystem,
+    /// Creating a file or directory inside the jail to serve as a mount point.
+    CreateMountPoint,
+    /// Bind mounting a path into the jail.
+    BindMount,
+    /// Remounting a bind mount with restrictive flags.
+    Remount,
+    /// Creating a scratch directory, or on Linux, mounting the `tmpfs` behind it.
+    CreateScratchDirectory,
+    /// Changing the root directory to the jail.
+    EnterJail,
+    /// Dropping capabilities with `capset`.
+    DropCapabilities,
+    /// Setting a resource limit with `setrlimit`.
+    SetResourceLimit,
+    /// Disabling core dumps and debugging with `prctl(PR_SET_DUMPABLE)`.
+    SetDumpable,
+    /// Entering a new session with `setsid`.
+    CreateSession,
+    /// Clearing the process environment down to the profile's allowlist.
+    ClearEnvironment,
+    /// Creating the Landlock ruleset.
+    CreateLandlockRuleset,
+    /// Adding a rule for a path or a TCP port to the Landlock ruleset.
+    AddLandlockRule,
+    /// Restricting the process with the Landlock ruleset.
+    EnforceLandlockRuleset,
+    /// Setting `PR_SET_NO_NEW_PRIVS`.
+    SetNoNewPrivileges,
+    /// Installing the `SIGSYS` handler that records trapped system calls.
+    InstallTrapHandler,
+    /// Installing the `seccomp-bpf` filter.
+    InstallSeccompFilter,
+    /// Handing the `seccomp` notification descriptor to the supervising process.
+    ContactSupervisor,
+    /// Applying the Seatbelt profile with `sandbox_init`.
+    InitSeatbelt,
+    /// Entering Capsicum capability mode.
+    EnterCapabilityMode,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match *self {
+            Stage::Spawn => "spawning the sandboxed process",
+            Stage::MakeMountsPrivate => "making mounts private",
+            Stage::CreateJailDirectory => "creating the jail directory",
+            Stage::MountJailFilesystem => "mounting the jail filesystem",
+            Stage::CreateMountPoint => "creating a mount point",
+            Stage::BindMount => "bind mounting",
+            Stage::Remount => "remounting",
+            Stage::CreateScratchDirectory => "creating a scratch directory",
+            Stage::EnterJail => "entering the jail",
+            Stage::DropCapabilities => "dropping capabilities",
+            Stage::SetResourceLimit => "setting a resource limit",
+            Stage::SetDumpable => "disabling debugging",
+            Stage::CreateSession => "creating a session",
+            Stage::ClearEnvironment => "clearing the environment",
+            Stage::CreateLandlockRuleset => "creating the Landlock ruleset",
+            Stage::AddLandlockRule => "adding a Landlock rule",
+            Stage::EnforceLandlockRuleset => "enforcing the Landlock ruleset",
+            Stage::SetNoNewPrivileges => "setting no_new_privs",
+            Stage::InstallTrapHandler => "installing the SIGSYS handler",
+            Stage::InstallSeccompFilter => "installing the seccomp filter",
+            Stage::ContactSupervisor => "contacting the supervisor",
+            Stage::InitSeatbelt => "initializing Seatbelt",
+            Stage::EnterCapabilityMode => "entering capability mode",
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::UnsupportedOperation(ref operation) => {
+                write!(formatter, "{:?} is not precisely supported on this platform", operation)
+            }
+            Error::InvalidOperation(ref operation) => {
+                write!(formatter, "{:?} is invalid", operation)
+            }
+            Error::InvalidViolationAction(action) => write!(formatter, "{:?} is invalid", action),
+            Error::OverlappingPatterns(ref a, ref b) => {
+                write!(formatter, "the patterns of {:?} and {:?} overlap", a, b)
+            }
+            Error::Os { stage, ref path, errno } => {
+                try!(write!(formatter, "{}", stage));
+                if let Some(ref path) = *path {
+                    try!(write!(formatter, " `{}`", path.display()));
+                }
+                write!(formatter, ": {}", io::Error::from_raw_os_error(errno))
+            }
+            Error::Platform { stage, ref message } => write!(formatter, "{}: {}", stage, message),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// Returns an `Error::Os` for the system call that just failed, taking the `errno` value from the
+/// calling thread.
+pub fn last_os_error(stage: Stage, path: Option<&Path>) -> Error {
+    io_error(stage, path, io::Error::last_os_error())
+}
+
+/// Returns an `Error::Os` describing a failed I/O operation.
+pub fn io_error(stage: Stage, path: Option<&Path>, error: io::Error) -> Error {
+    Error::Os {
+        stage,
+        path: path.map(|path| path.to_path_buf()),
+        errno: error.raw_os_error().unwrap_or(libc::EIO),
+    }
+}
@@ -10,8 +10,7 @@
 
 extern crate gaol;
 
-use gaol::profile::{AddressPattern, Operation, OperationSupport, OperationSupportLevel};
-use gaol::profile::{PathPattern, Profile};
+use gaol::profile::{AddressPattern, Operation, PathPattern, Profile, Strictness};
 use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
 use std::env;
 use std::fs::File;
@@ -20,7 +19,7 @@ use std::path::PathBuf;
 // Create the sandbox profile.
 fn profile() -> Profile {
     // Set up the list of desired operations.
-    let mut operations = vec![
+    let operations = vec![
         Operation::FileReadAll(PathPattern::Subpath(PathBuf::from("/lib"))),
         Operation::FileReadAll(PathPattern::Literal(PathBuf::from("/etc"))),
         Operation::NetworkOutbound(AddressPattern::All),
@@ -29,15 +28,12 @@ fn profile() -> Profile {
 
     // Remove operations not supported by this OS. (Otherwise the creation of the profile will
     // fail.)
-    operations.retain(|operation| {
-        println!("{:?}: {:?}", operation, operation.support());
-        match operation.support() {
-            OperationSupportLevel::NeverAllowed | OperationSupportLevel::CanBeAllowed => true,
-            _ => false,
-        }
-    });
-
-    Profile::new(operations).unwrap()
+    let (profile, downgrades) =
+        Profile::with_strictness(operations, Strictness::DropUnsupported).unwrap();
+    for downgrade in downgrades.iter() {
+        println!("{:?}: {:?}", downgrade.requested, downgrade.support);
+    }
+    profile
 }
 
 fn main() {
@@ -0,0 +1,237 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Matching, expansion, and translation of the glob patterns used by `PathPattern::Glob`.
+//!
+//! `*` matches any sequence of characters other than `/`, `?` matches any single character other
+//! than `/`, and `[...]` matches one character from the given set, which may contain ranges like
+//! `a-z` and is negated by a leading `!` or `^`. A backslash matches the character after it
+//! literally. Wildcards never match `/`, so each one stays within a single path component, but
+//! they do match a `.` at the start of a name.
+
+#[cfg(any(target_os="android", target_os="linux"))]
+use std::fs;
+use std::path::PathBuf;
+
+/// Returns true if `text` matches the glob `pattern` in its entirety.
+pub fn matches(pattern: &str, text: &str) -> bool {
+    matches_bytes(pattern.as_bytes(), text.as_bytes())
+}
+
+fn matches_bytes(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((&b'*', rest)) => {
+            // Try every possible length for the run of characters that the star matches.
+            let mut index = 0;
+            loop {
+                if matches_bytes(rest, &text[index..]) {
+                    return true
+                }
+                if index == text.len() || text[index] == b'/' {
+                    return false
+                }
+                index += 1
+            }
+        }
+        Some((&b'?', rest)) => {
+            match text.split_first() {
+                Some((&byte, text)) if byte != b'/' => matches_bytes(rest, text),
+                _ => false,
+            }
+        }
+        Some((&b'[', rest)) if class_length(rest).is_some() => {
+            let length = class_length(rest).unwrap();
+            match text.split_first() {
+                Some((&byte, text)) if byte != b'/' && class_matches(&rest[..length], byte) => {
+                    matches_bytes(&rest[length + 1..], text)
+                }
+                _ => false,
+            }
+        }
+        Some((&b'\\', rest)) if !rest.is_empty() => {
+            text.first() == rest.first() && matches_bytes(&rest[1..], &text[1..])
+        }
+        Some((&byte, rest)) => text.first() == Some(&byte) && matches_bytes(rest, &text[1..]),
+    }
+}
+
+/// Given the part of a pattern after an opening `[`, returns the length of the character class
+/// up to (but not including) the closing `]`, or `None` if the class is unterminated (in which
+/// case the `[` is treated literally).
+fn class_length(pattern: &[u8]) -> Option<usize> {
+    let mut index = 0;
+    if index < pattern.len() && (pattern[index] == b'!' || pattern[index] == b'^') {
+        index += 1
+    }
+    // A `]` right at the start of the class is a literal `]`.
+    if index < pattern.len() && pattern[index] == b']' {
+        index += 1
+    }
+    pattern[index..].iter().position(|&byte| byte == b']').map(|position| index + position)
+}
+
+fn class_matches(class: &[u8], byte: u8) -> bool {
+    let (negated, mut class) = match class.split_first() {
+        Some((&b'!', rest)) | Some((&b'^', rest)) => (true, rest),
+        _ => (false, class),
+    };
+    let mut matched = false;
+    while let Some((&first, rest)) = class.split_first() {
+        if rest.len() >= 2 && rest[0] == b'-' {
+            if first <= byte && byte <= rest[1] {
+                matched = true
+            }
+            class = &rest[2..]
+        } else {
+            if first == byte {
+                matched = true
+            }
+            class = rest
+        }
+    }
+    matched != negated
+}
+
+/// Returns true if `pattern` contains no wildcards, so that it only matches itself.
+pub fn is_literal(pattern: &str) -> bool {
+    !pattern.bytes().any(|byte| byte == b'*' || byte == b'?' || byte == b'[' || byte == b'\\')
+}
+
+/// Returns true if some path could match both of the absolute glob patterns `a` and `b`. This errs
+/// on the side of caution: two wildcard components are always assumed to match a common name.
+pub fn may_overlap(a: &str, b: &str) -> bool {
+    let (a, b) = (components(a), components(b));
+    a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| components_may_overlap(a, b))
+}
+
+/// Returns true if some path within the directory `subpath`, or `subpath` itself, could match the
+/// absolute glob `pattern`.
+pub fn may_match_within(pattern: &str, subpath: &str) -> bool {
+    let (pattern, subpath) = (components(pattern), components(subpath));
+    pattern.len() >= subpath.len() &&
+        pattern.iter().zip(subpath.iter()).all(|(pattern, name)| matches(pattern, name))
+}
+
+/// Returns true if every path that matches the absolute glob `pattern` lies within the directory
+/// `subpath` (or is `subpath` itself).
+pub fn is_within(pattern: &str, subpath: &str) -> bool {
+    let (pattern, subpath) = (components(pattern), components(subpath));
+    pattern.len() >= subpath.len() &&
+        pattern.iter().zip(subpath.iter()).all(|(pattern, name)| {
+            is_literal(pattern) && pattern == name
+        })
+}
+
+/// Returns the deepest directory that contains every path matching the absolute glob `pattern`:
+/// the components before the first one with a wildcard in it.
+pub fn literal_prefix(pattern: &str) -> PathBuf {
+    let mut components = components(pattern);
+    // Even if the pattern has no wildcards at all, the last component names the matched file
+    // itself rather than a directory containing it.
+    components.pop();
+    let mut prefix = PathBuf::from("/");
+    for component in components.into_iter().take_while(|component| is_literal(component)) {
+        prefix.push(component)
+    }
+    prefix
+}
+
+fn components(pattern: &str) -> Vec<&str> {
+    pattern.split('/').filter(|component| !component.is_empty()).collect()
+}
+
+fn components_may_overlap(a: &str, b: &str) -> bool {
+    match (is_literal(a), is_literal(b)) {
+        (true, true) => a == b,
+        (true, false) => matches(b, a),
+        (false, true) => matches(a, b),
+        (false, false) => true,
+    }
+}
+
+/// Returns every existing path on the filesystem that matches the absolute glob `pattern`.
+#[cfg(any(target_os="android", target_os="linux"))]
+pub fn expand(pattern: &str) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from("/")];
+    for component in pattern.split('/').filter(|component| !component.is_empty()) {
+        let mut next_paths = vec![];
+        for path in paths.into_iter() {
+            if is_literal(component) {
+                let path = path.join(component);
+                if fs::symlink_metadata(&path).is_ok() {
+                    next_paths.push(path)
+                }
+                continue
+            }
+            let entries = match fs::read_dir(&path) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(_) => continue,
+                };
+                if entry.file_name().to_str().is_some_and(|name| matches(component, name)) {
+                    next_paths.push(entry.path())
+                }
+            }
+        }
+        paths = next_paths
+    }
+    paths.sort();
+    paths
+}
+
+/// Translates `pattern` into an equivalent anchored POSIX extended regular expression.
+#[cfg(target_os="macos")]
+pub fn to_regex(pattern: &str) -> String {
+    let pattern = pattern.as_bytes();
+    let mut regex = vec![b'^'];
+    let mut index = 0;
+    while index < pattern.len() {
+        match pattern[index] {
+            b'*' => regex.extend_from_slice(b"[^/]*"),
+            b'?' => regex.extend_from_slice(b"[^/]"),
+            b'[' if class_length(&pattern[index + 1..]).is_some() => {
+                let length = class_length(&pattern[index + 1..]).unwrap();
+                let class = &pattern[index + 1..index + 1 + length];
+                regex.push(b'[');
+                match class.split_first() {
+                    Some((&b'!', rest)) | Some((&b'^', rest)) => {
+                        // Negated classes still mustn't match a `/`.
+                        regex.extend_from_slice(b"^/");
+                        regex.extend_from_slice(rest)
+                    }
+                    _ => regex.extend_from_slice(class),
+                }
+                regex.push(b']');
+                index += length + 1
+            }
+            b'\\' if index + 1 < pattern.len() => {
+                index += 1;
+                push_literal(&mut regex, pattern[index])
+            }
+            byte => push_literal(&mut regex, byte),
+        }
+        index += 1
+    }
+    regex.push(b'$');
+    String::from_utf8(regex).unwrap()
+}
+
+#[cfg(target_os="macos")]
+fn push_literal(regex: &mut Vec<u8>, byte: u8) {
+    if b".^$|()[]{}*+?\\".contains(&byte) {
+        regex.push(b'\\')
+    }
+    regex.push(byte)
+}
@@ -12,9 +12,22 @@
 extern crate log;
 
 extern crate libc;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+#[cfg(feature = "serde")]
+extern crate toml;
+
+pub use error::{Error, Stage};
 
 pub mod profile;
 pub mod sandbox;
+#[cfg(feature = "serde")]
+pub mod loader;
+
+mod error;
+mod glob;
 
 pub mod platform {
     #[cfg(any(target_os="android", target_os="linux"))]
@@ -25,6 +38,8 @@ pub mod platform {
     pub use platform::freebsd::{ChildSandbox, Operation, Sandbox};
     #[cfg(any(target_os="android", target_os="linux", target_os="macos", target_os="freebsd"))]
     pub use platform::unix::process::{self, Process};
+    #[cfg(any(target_os="android", target_os="linux", target_os="macos", target_os="freebsd"))]
+    pub use platform::unix::broker;
 
     #[cfg(any(target_os="android", target_os="linux"))]
     pub mod linux;
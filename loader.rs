@@ -0,0 +1,522 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Loading and saving profiles as TOML or JSON files.
+//!
+//! A profile file contains a single `operations` list, using the `snake_case` names of the
+//! variants of `Operation` and the patterns inside them. In TOML:
+//!
+//! ```toml
+//! operations = [
+//!     { file_read_all = { subpath = "$HOME/.config/app" } },
+//!     { file_write = { glob = "$TMPDIR/app-*.log" } },
+//!     { network_outbound = { ipv4_network = "10.0.0.0/8:443" } },
+//!     "signal_self",
+//! ]
+//! ```
+//!
+//! An optional `limits` table sets the profile's resource limits, using the field names of
+//! `ResourceLimits`:
+//!
+//! ```toml
+//! [limits]
+//! cpu_seconds = 10
+//! address_space_bytes = 1073741824
+//! ```
+//!
+//! An optional `environment` list names the environment variables that survive sandbox
+//! activation, each with an optional fixed value:
+//!
+//! ```toml
+//! environment = [
+//!     { name = "RUST_LOG" },
+//!     { name = "LANG", value = "C.UTF-8" },
+//! ]
+//! ```
+//!
+//! An optional `violations` table sets what happens when the sandboxed process makes a system call
+//! that the profile doesn't allow, using the field names of `ViolationPolicy`:
+//!
+//! ```toml
+//! [violations]
+//! default_action = { errno = 1 }
+//! overrides = [
+//!     { syscall = 101, action = "kill_process" },
+//! ]
+//! ```
+//!
+//! IP networks are written as an address and prefix length, optionally followed by a port or an
+//! inclusive range of ports: `10.0.0.0/8`, `::1/128:8080`, or `fd00::/8:8000-8999`.
+//!
+//! Every string in an entry may refer to variables as `$NAME` or `${NAME}`; `$$` stands for a
+//! literal `$`. See `Variables` for the ones that are defined by default.
+
+use error::Error;
+use profile::{Operation, OperationSupport, OperationSupportLevel, PortRange, Profile};
+use profile::{EnvironmentVariable, ResourceLimits, ViolationPolicy};
+
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use serde_json::{self, Value};
+use std::collections::HashMap;
+use std::env;
+use std::error;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+use toml;
+
+/// The syntax of a profile file.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Format {
+    /// TOML, with the extension `.toml`.
+    Toml,
+    /// JSON, with the extension `.json`.
+    Json,
+}
+
+/// The variables that may be referred to from the strings in a profile file.
+#[derive(Clone, Debug)]
+pub struct Variables {
+    values: HashMap<String, String>,
+}
+
+impl Default for Variables {
+    fn default() -> Variables {
+        Variables::new()
+    }
+}
+
+impl Variables {
+    /// Creates a set of variables containing `HOME`, if it is set in the environment, and
+    /// `TMPDIR`, which is always the system temporary directory.
+    pub fn new() -> Variables {
+        let mut variables = Variables {
+            values: HashMap::new(),
+        };
+        if let Some(home) = env::var_os("HOME") {
+            variables.set("HOME", home.to_string_lossy().into_owned());
+        }
+        variables.set("TMPDIR", env::temp_dir().to_string_lossy().into_owned());
+        variables
+    }
+
+    /// Defines the variable `name`, replacing any previous definition.
+    pub fn set<N, V>(&mut self, name: N, value: V) -> &mut Variables
+                     where N: Into<String>, V: Into<String> {
+        self.values.insert(name.into(), value.into());
+        self
+    }
+
+    /// Replaces the variable references in `string` with their values.
+    fn expand(&self, string: &str) -> Result<String, String> {
+        let mut result = String::new();
+        let mut rest = string;
+        while let Some(position) = rest.find('$') {
+            result.push_str(&rest[..position]);
+            rest = &rest[position + 1..];
+            let (name, after) = if rest.starts_with('$') {
+                result.push('$');
+                rest = &rest[1..];
+                continue
+            } else if rest.starts_with('{') {
+                match rest.find('}') {
+                    Some(end) => (&rest[1..end], &rest[end + 1..]),
+                    None => return Err(format!("unterminated variable reference in `{}`", string)),
+                }
+            } else {
+                let end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                              .unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            };
+            match self.values.get(name) {
+                Some(value) if !name.is_empty() => result.push_str(value),
+                _ => return Err(format!("undefined variable `{}` in `{}`", name, string)),
+            }
+            rest = after
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+}
+
+/// An error encountered while loading or saving a profile.
+#[derive(Clone, Debug)]
+pub struct LoadError {
+    /// The index of the offending entry in the `operations` list, if the error concerns a single
+    /// entry.
+    pub entry: Option<usize>,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl LoadError {
+    fn new<M>(message: M) -> LoadError where M: fmt::Display {
+        LoadError {
+            entry: None,
+            message: message.to_string(),
+        }
+    }
+
+    fn at_entry<M>(entry: usize, message: M) -> LoadError where M: fmt::Display {
+        LoadError {
+            entry: Some(entry),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.entry {
+            Some(entry) => write!(formatter, "operations[{}]: {}", entry, self.message),
+            None => formatter.write_str(&self.message),
+        }
+    }
+}
+
+impl error::Error for LoadError {}
+
+/// Loads a profile from the file at `path`, whose format is determined by its extension.
+pub fn load(path: &Path, variables: &Variables) -> Result<Profile, LoadError> {
+    let format = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => Format::Toml,
+        Some("json") => Format::Json,
+        _ => {
+            return Err(LoadError::new(format!("`{}` has neither a `.toml` nor a `.json` extension",
+                                              path.display())))
+        }
+    };
+    let mut text = String::new();
+    if let Err(error) = File::open(path).and_then(|mut file| file.read_to_string(&mut text)) {
+        return Err(LoadError::new(format!("couldn't read `{}`: {}", path.display(), error)))
+    }
+    from_str(&text, format, variables)
+}
+
+/// Parses a profile from `text`.
+///
+/// Every entry is checked individually, so that an error names the entry at fault: whether it
+/// fails to parse, refers to an undefined variable, cannot be allowed precisely on this platform,
+/// or is rejected by `Profile::new()`, as when its pattern overlaps another entry's.
+pub fn from_str(text: &str, format: Format, variables: &Variables) -> Result<Profile, LoadError> {
+    let document: Value = match format {
+        Format::Toml => try!(toml::from_str(text).map_err(LoadError::new)),
+        Format::Json => try!(serde_json::from_str(text).map_err(LoadError::new)),
+    };
+    let mut fields = match document {
+        Value::Object(fields) => fields,
+        _ => return Err(LoadError::new("a profile must be a table")),
+    };
+    let entries = match fields.remove("operations") {
+        Some(Value::Array(entries)) => entries,
+        Some(_) => return Err(LoadError::new("`operations` must be a list")),
+        None => return Err(LoadError::new("missing field `operations`")),
+    };
+    let resource_limits: ResourceLimits = match fields.remove("limits") {
+        Some(limits) => {
+            try!(serde_json::from_value(limits).map_err(|error| {
+                LoadError::new(format!("limits: {}", error))
+            }))
+        }
+        None => ResourceLimits::default(),
+    };
+    let environment: Vec<EnvironmentVariable> = match fields.remove("environment") {
+        Some(mut environment) => {
+            try!(expand_strings(&mut environment, variables).map_err(|message| {
+                LoadError::new(format!("environment: {}", message))
+            }));
+            try!(serde_json::from_value(environment).map_err(|error| {
+                LoadError::new(format!("environment: {}", error))
+            }))
+        }
+        None => vec![],
+    };
+    let violation_policy: ViolationPolicy = match fields.remove("violations") {
+        Some(violations) => {
+            try!(serde_json::from_value(violations).map_err(|error| {
+                LoadError::new(format!("violations: {}", error))
+            }))
+        }
+        None => ViolationPolicy::default(),
+    };
+    if let Some(unknown) = fields.keys().next() {
+        return Err(LoadError::new(format!("unknown field `{}`", unknown)))
+    }
+
+    let mut operations = vec![];
+    for (index, mut entry) in entries.into_iter().enumerate() {
+        try!(expand_strings(&mut entry, variables).map_err(|message| {
+            LoadError::at_entry(index, message)
+        }));
+        let operation: Operation = try!(serde_json::from_value(entry).map_err(|error| {
+            LoadError::at_entry(index, error)
+        }));
+        match operation.support() {
+            OperationSupportLevel::NeverAllowed | OperationSupportLevel::CanBeAllowed => {}
+            OperationSupportLevel::CannotBeAllowedPrecisely => {
+                return Err(LoadError::at_entry(index, format!(
+                    "{:?} cannot be allowed precisely on this platform", operation)))
+            }
+            OperationSupportLevel::AlwaysAllowed => {
+                return Err(LoadError::at_entry(index, format!(
+                    "{:?} is always allowed on this platform", operation)))
+            }
+        }
+        operations.push(operation)
+    }
+    let mut profile = try!(Profile::new(operations.clone()).map_err(|error| {
+        profile_error(error, &operations)
+    }));
+    profile.set_resource_limits(resource_limits);
+    profile.set_environment(environment);
+    try!(profile.set_violation_policy(violation_policy).map_err(|error| {
+        LoadError::new(format!("violations: {}", error))
+    }));
+    Ok(profile)
+}
+
+/// Turns an error from `Profile::new()` into a `LoadError` naming the entry of `operations` at
+/// fault, if there is one.
+fn profile_error(error: Error, operations: &[Operation]) -> LoadError {
+    let index = match error {
+        Error::UnsupportedOperation(ref operation) |
+        Error::InvalidOperation(ref operation) |
+        Error::OverlappingPatterns(_, ref operation) => {
+            operations.iter().position(|entry| entry == operation)
+        }
+        _ => None,
+    };
+    match index {
+        Some(index) => LoadError::at_entry(index, error),
+        None => LoadError::new(error),
+    }
+}
+
+/// Writes `profile` out in the given format. Variables are not reintroduced, but each `$` in the
+/// strings that `from_str` expands is written as `$$`, so that the text loads back as the same
+/// profile.
+pub fn to_string(profile: &Profile, format: Format) -> Result<String, LoadError> {
+    let mut operations = try!(serde_json::to_value(profile.allowed_operations())
+                                  .map_err(LoadError::new));
+    let mut environment = try!(serde_json::to_value(profile.environment())
+                                   .map_err(LoadError::new));
+    escape_strings(&mut operations);
+    escape_strings(&mut environment);
+    let file = EscapedProfileFile {
+        operations,
+        limits: profile.resource_limits(),
+        environment,
+        violations: profile.violation_policy(),
+    };
+    match format {
+        Format::Toml => toml::to_string(&file).map_err(LoadError::new),
+        Format::Json => serde_json::to_string_pretty(&file).map_err(LoadError::new),
+    }
+}
+
+fn expand_strings(value: &mut Value, variables: &Variables) -> Result<(), String> {
+    match *value {
+        Value::String(ref mut string) => *string = try!(variables.expand(string)),
+        Value::Array(ref mut values) => {
+            for value in values.iter_mut() {
+                try!(expand_strings(value, variables))
+            }
+        }
+        Value::Object(ref mut fields) => {
+            for (_, value) in fields.iter_mut() {
+                try!(expand_strings(value, variables))
+            }
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+    Ok(())
+}
+
+/// Writes `$` as `$$` in every string, undoing what `expand_strings` does to it.
+fn escape_strings(value: &mut Value) {
+    match *value {
+        Value::String(ref mut string) => *string = string.replace('$', "$$"),
+        Value::Array(ref mut values) => {
+            for value in values.iter_mut() {
+                escape_strings(value)
+            }
+        }
+        Value::Object(ref mut fields) => {
+            for (_, value) in fields.iter_mut() {
+                escape_strings(value)
+            }
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
+fn is_empty_list(value: &Value) -> bool {
+    value.as_array().is_some_and(|values| values.is_empty())
+}
+
+/// A profile as `to_string` writes it, with its operations and environment already escaped.
+#[derive(Serialize)]
+struct EscapedProfileFile<'a> {
+    operations: Value,
+    #[serde(skip_serializing_if = "ResourceLimits::is_unlimited")]
+    limits: &'a ResourceLimits,
+    #[serde(skip_serializing_if = "is_empty_list")]
+    environment: Value,
+    #[serde(skip_serializing_if = "ViolationPolicy::is_default")]
+    violations: &'a ViolationPolicy,
+}
+
+#[derive(Serialize)]
+struct ProfileFileRef<'a> {
+    operations: &'a [Operation],
+    #[serde(skip_serializing_if = "ResourceLimits::is_unlimited")]
+    limits: &'a ResourceLimits,
+    #[serde(skip_serializing_if = "<[EnvironmentVariable]>::is_empty")]
+    environment: &'a [EnvironmentVariable],
+    #[serde(skip_serializing_if = "ViolationPolicy::is_default")]
+    violations: &'a ViolationPolicy,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileFile {
+    operations: Vec<Operation>,
+    #[serde(default)]
+    limits: ResourceLimits,
+    #[serde(default)]
+    environment: Vec<EnvironmentVariable>,
+    #[serde(default)]
+    violations: ViolationPolicy,
+}
+
+impl Serialize for Profile {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        ProfileFileRef {
+            operations: self.allowed_operations(),
+            limits: self.resource_limits(),
+            environment: self.environment(),
+            violations: self.violation_policy(),
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Profile {
+    fn deserialize<D>(deserializer: D) -> Result<Profile, D::Error> where D: Deserializer<'de> {
+        let file = try!(ProfileFile::deserialize(deserializer));
+        let mut profile = try!(Profile::new(file.operations.clone()).map_err(|error| {
+            de::Error::custom(profile_error(error, &file.operations))
+        }));
+        profile.set_resource_limits(file.limits);
+        profile.set_environment(file.environment);
+        try!(profile.set_violation_policy(file.violations).map_err(de::Error::custom));
+        Ok(profile)
+    }
+}
+
+/// Formats an IP network in the `address/prefix_length[:first[-last]]` syntax.
+fn format_network<A>(address: &A, prefix_length: u8, ports: &Option<PortRange>) -> String
+                     where A: fmt::Display {
+    match *ports {
+        None => format!("{}/{}", address, prefix_length),
+        Some(ports) if ports.first == ports.last => {
+            format!("{}/{}:{}", address, prefix_length, ports.first)
+        }
+        Some(ports) => format!("{}/{}:{}-{}", address, prefix_length, ports.first, ports.last),
+    }
+}
+
+/// Parses an IP network in the `address/prefix_length[:first[-last]]` syntax.
+fn parse_network<A>(string: &str, max_prefix_length: u8)
+                    -> Result<(A, u8, Option<PortRange>), String>
+                    where A: FromStr {
+    let invalid = || format!("invalid network `{}`", string);
+    let slash = try!(string.rfind('/').ok_or_else(&invalid));
+    let address = try!(string[..slash].parse().map_err(|_| invalid()));
+    let (prefix_length, ports) = match string[slash + 1..].find(':') {
+        None => (&string[slash + 1..], None),
+        Some(colon) => (&string[slash + 1..slash + 1 + colon], Some(&string[slash + 2 + colon..])),
+    };
+    let prefix_length: u8 = try!(prefix_length.parse().map_err(|_| invalid()));
+    if prefix_length > max_prefix_length {
+        return Err(format!("prefix length {} is too long in `{}`", prefix_length, string))
+    }
+    let ports = match ports {
+        None => None,
+        Some(ports) => {
+            let (first, last) = match ports.find('-') {
+                None => (ports, ports),
+                Some(dash) => (&ports[..dash], &ports[dash + 1..]),
+            };
+            let range = PortRange {
+                first: try!(first.parse().map_err(|_| invalid())),
+                last: try!(last.parse().map_err(|_| invalid())),
+            };
+            if range.first > range.last {
+                return Err(format!("empty port range in `{}`", string))
+            }
+            Some(range)
+        }
+    };
+    Ok((address, prefix_length, ports))
+}
+
+#[doc(hidden)]
+pub mod ipv4_network {
+    use profile::PortRange;
+
+    use serde::de::{self, Deserialize, Deserializer};
+    use serde::ser::Serializer;
+    use std::net::Ipv4Addr;
+
+    pub fn serialize<S>(address: &Ipv4Addr,
+                        prefix_length: &u8,
+                        ports: &Option<PortRange>,
+                        serializer: S)
+                        -> Result<S::Ok, S::Error>
+                        where S: Serializer {
+        serializer.serialize_str(&super::format_network(address, *prefix_length, ports))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D)
+                               -> Result<(Ipv4Addr, u8, Option<PortRange>), D::Error>
+                               where D: Deserializer<'de> {
+        let string = try!(String::deserialize(deserializer));
+        super::parse_network(&string, 32).map_err(de::Error::custom)
+    }
+}
+
+#[doc(hidden)]
+pub mod ipv6_network {
+    use profile::PortRange;
+
+    use serde::de::{self, Deserialize, Deserializer};
+    use serde::ser::Serializer;
+    use std::net::Ipv6Addr;
+
+    pub fn serialize<S>(address: &Ipv6Addr,
+                        prefix_length: &u8,
+                        ports: &Option<PortRange>,
+                        serializer: S)
+                        -> Result<S::Ok, S::Error>
+                        where S: Serializer {
+        serializer.serialize_str(&super::format_network(address, *prefix_length, ports))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D)
+                               -> Result<(Ipv6Addr, u8, Option<PortRange>), D::Error>
+                               where D: Deserializer<'de> {
+        let string = try!(String::deserialize(deserializer));
+        super::parse_network(&string, 128).map_err(de::Error::custom)
+    }
+}
@@ -10,12 +10,17 @@
 
 //! Sandboxing on FreeBSD via Capsicum.
 
+use error::{self, Error, Stage};
+use platform::unix::{environment, limits};
+use platform::unix::broker::BrokerClient;
 use platform::unix::process::Process;
 use profile::{self, OperationSupport, OperationSupportLevel, Profile};
 use sandbox::{ChildSandboxMethods, Command, SandboxMethods};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use libc::c_int;
-use std::io;
 
 impl OperationSupport for profile::Operation {
     fn support(&self) -> OperationSupportLevel {
@@ -27,7 +32,8 @@ impl OperationSupport for profile::Operation {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Operation { }
 
 pub struct Sandbox {
@@ -47,30 +53,42 @@ impl SandboxMethods for Sandbox {
         &self.profile
     }
 
-    fn start(&self, command: &mut Command) -> io::Result<Process> {
-        command.env("GAOL_CHILD_PROCESS", "1").spawn()
+    fn start(&self, command: &mut Command) -> Result<Process,Error> {
+        command.env("GAOL_CHILD_PROCESS", "1").spawn().map_err(|error| {
+            error::io_error(Stage::Spawn, None, error)
+        })
     }
 }
 
 pub struct ChildSandbox {
+    profile: Profile,
+    broker: Option<BrokerClient>,
 }
 
 impl ChildSandbox {
-    pub fn new(_profile: Profile) -> ChildSandbox {
+    pub fn new(profile: Profile) -> ChildSandbox {
         ChildSandbox {
+            profile: profile,
+            broker: BrokerClient::from_environment(),
         }
     }
 }
 
 impl ChildSandboxMethods for ChildSandbox {
-    fn activate(&self) -> Result<(),()> {
+    fn activate(&self) -> Result<(),Error> {
+        try!(limits::activate(self.profile.resource_limits()));
+        try!(environment::activate(self.profile.environment()));
         if unsafe { cap_enter() } == 0 {
             Ok(())
         } else {
             error!("Failed to init sandbox");
-            Err(())
+            Err(error::last_os_error(Stage::EnterCapabilityMode, None))
         }
     }
+
+    fn broker(&self) -> Option<&BrokerClient> {
+        self.broker.as_ref()
+    }
 }
 
 extern {
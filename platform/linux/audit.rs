@@ -0,0 +1,109 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Audit mode, for working out what a program needs from its profile.
+//!
+//! A process started with `Sandbox::start_audited` that activates `ChildSandbox::audit` runs
+//! without the namespace jail, Landlock, or the other miscellaneous restrictions, so it sees the
+//! normal filesystem. Its `seccomp` filter allows what the profile allows and hands every other
+//! system call to the supervisor in the parent, along with the calls that open files, execute
+//! programs, or connect sockets, so that their paths and addresses can be checked against the
+//! profile. The supervisor records whatever the profile wouldn't have allowed and lets the call
+//! go ahead.
+//!
+//! Audit mode doesn't enforce anything, so only run programs in it that you would trust outside
+//! the sandbox.
+
+use error::{self, Error, Stage};
+use platform::linux::supervisor;
+use platform::unix;
+use platform::unix::process::Process;
+use profile::{Operation, Profile};
+use sandbox::Command;
+
+use libc;
+use std::ffi::CString;
+use std::sync::{Arc, Mutex};
+
+/// Something that a process in audit mode did that its profile wouldn't have allowed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation {
+    /// The profile would need this operation in order to allow what the process did.
+    Operation(Operation),
+    /// The process made a system call, identified by its number, that the `seccomp` filter would
+    /// have refused.
+    Syscall(u32),
+}
+
+/// The violations that processes in audit mode have committed so far, which the supervisor adds
+/// to as it goes.
+///
+/// Clones share the same list, so keep one around to look at after the process has exited.
+#[derive(Clone, Debug, Default)]
+pub struct AuditReport {
+    violations: Arc<Mutex<Vec<Violation>>>,
+}
+
+impl AuditReport {
+    /// Creates an empty report.
+    pub fn new() -> AuditReport {
+        AuditReport {
+            violations: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Returns the violations recorded so far, each one once, in the order they first happened.
+    ///
+    /// The process waits for each of its violations to be recorded, so the report is complete
+    /// once `Process::wait` has returned.
+    pub fn violations(&self) -> Vec<Violation> {
+        self.violations.lock().unwrap().clone()
+    }
+
+    /// Adds `violation` to the report, unless it is already there.
+    pub fn record(&self, violation: Violation) {
+        let mut violations = self.violations.lock().unwrap();
+        if !violations.contains(&violation) {
+            violations.push(violation)
+        }
+    }
+}
+
+/// Spawns a child process to be audited, outside of any namespace, and starts supervising it.
+pub fn start(profile: &Profile, command: &mut Command, report: AuditReport)
+             -> Result<Process,Error> {
+    let channel = try!(supervisor::Channel::new());
+    let (name, value) = channel.environment_variable();
+    command.env(name, value);
+    let name = CString::new(name).unwrap();
+
+    unsafe {
+        let pid = libc::fork();
+        if pid < 0 {
+            let error = error::last_os_error(Stage::Spawn, None);
+            command.env.remove(&name);
+            return Err(error)
+        }
+        if pid == 0 {
+            channel.inherit();
+            drop(unix::process::exec(command));
+            libc::abort()
+        }
+
+        // Only the child needs the variable; leave the caller's command as it was.
+        command.env.remove(&name);
+        channel.audit(profile.clone(), report);
+        Ok(Process {
+            pid,
+            cpu_time_limit: None,
+            scratch_directories: vec![],
+        })
+    }
+}
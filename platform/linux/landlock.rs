@@ -0,0 +1,382 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Landlock support on Linux 5.13 and later.
+//!
+//! This stacks on top of `namespace`: the `chroot` jail decides which paths exist at all, while
+//! Landlock decides what may be done with each of them. That lets us allow metadata reads of a
+//! path without allowing its contents to be read, which the jail alone can't express. Landlock
+//! never restricts `stat`, so a path that is mounted into the jail but not granted any rights
+//! here is exactly a path whose metadata may be read.
+//!
+//! From ABI version 4, Landlock can also restrict which TCP ports sockets may be bound and
+//! connected to, which is how per-port network operations are enforced.
+
+use error::{self, Error, Stage};
+use platform::linux::namespace;
+use profile::{AddressPattern, Operation, PathPattern, Profile};
+
+use libc::{self, c_int, c_void};
+use std::ffi::CString;
+use std::mem;
+use std::os::unix::prelude::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1 << 0;
+const LANDLOCK_RULE_PATH_BENEATH: c_int = 1;
+const LANDLOCK_RULE_NET_PORT: c_int = 2;
+
+const LANDLOCK_ACCESS_FS_EXECUTE: u64 = 1 << 0;
+const LANDLOCK_ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+const LANDLOCK_ACCESS_FS_READ_FILE: u64 = 1 << 2;
+const LANDLOCK_ACCESS_FS_READ_DIR: u64 = 1 << 3;
+const LANDLOCK_ACCESS_FS_REMOVE_DIR: u64 = 1 << 4;
+const LANDLOCK_ACCESS_FS_REMOVE_FILE: u64 = 1 << 5;
+const LANDLOCK_ACCESS_FS_MAKE_CHAR: u64 = 1 << 6;
+const LANDLOCK_ACCESS_FS_MAKE_DIR: u64 = 1 << 7;
+const LANDLOCK_ACCESS_FS_MAKE_REG: u64 = 1 << 8;
+const LANDLOCK_ACCESS_FS_MAKE_SOCK: u64 = 1 << 9;
+const LANDLOCK_ACCESS_FS_MAKE_FIFO: u64 = 1 << 10;
+const LANDLOCK_ACCESS_FS_MAKE_BLOCK: u64 = 1 << 11;
+const LANDLOCK_ACCESS_FS_MAKE_SYM: u64 = 1 << 12;
+/// Added in ABI version 2.
+const LANDLOCK_ACCESS_FS_REFER: u64 = 1 << 13;
+/// Added in ABI version 3.
+const LANDLOCK_ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+/// Added in ABI version 5.
+const LANDLOCK_ACCESS_FS_IOCTL_DEV: u64 = 1 << 15;
+
+/// Added in ABI version 4, along with the rest of network support.
+const LANDLOCK_ACCESS_NET_BIND_TCP: u64 = 1 << 0;
+/// Added in ABI version 4.
+const LANDLOCK_ACCESS_NET_CONNECT_TCP: u64 = 1 << 1;
+
+/// The first ABI version that can restrict TCP ports.
+const NETWORK_ABI_VERSION: u32 = 4;
+
+/// The rights that only make sense for files, as opposed to directories. The kernel rejects rules
+/// that grant any other rights on a file.
+const FILE_ACCESS: u64 = LANDLOCK_ACCESS_FS_EXECUTE | LANDLOCK_ACCESS_FS_WRITE_FILE |
+    LANDLOCK_ACCESS_FS_READ_FILE | LANDLOCK_ACCESS_FS_TRUNCATE | LANDLOCK_ACCESS_FS_IOCTL_DEV;
+
+#[repr(C)]
+struct landlock_ruleset_attr {
+    handled_access_fs: u64,
+    /// Only passed to the kernel from ABI version 4; older kernels reject the larger structure.
+    handled_access_net: u64,
+}
+
+#[repr(C, packed)]
+struct landlock_path_beneath_attr {
+    allowed_access: u64,
+    parent_fd: c_int,
+}
+
+#[repr(C)]
+struct landlock_net_port_attr {
+    allowed_access: u64,
+    port: u64,
+}
+
+/// Returns the Landlock ABI version that the running kernel supports, or `None` if Landlock is
+/// unsupported or disabled.
+pub fn abi_version() -> Option<u32> {
+    let version = unsafe {
+        libc::syscall(libc::SYS_landlock_create_ruleset,
+                      ptr::null::<landlock_ruleset_attr>(),
+                      0_usize,
+                      LANDLOCK_CREATE_RULESET_VERSION)
+    };
+    if version > 0 {
+        Some(version as u32)
+    } else {
+        None
+    }
+}
+
+/// Returns true if the running kernel can restrict TCP ports.
+pub fn supports_network() -> bool {
+    abi_version().is_some_and(|abi_version| abi_version >= NETWORK_ABI_VERSION)
+}
+
+/// Returns every filesystem right that the given ABI version knows about.
+fn handled_access(abi_version: u32) -> u64 {
+    let mut access = LANDLOCK_ACCESS_FS_EXECUTE | LANDLOCK_ACCESS_FS_WRITE_FILE |
+        LANDLOCK_ACCESS_FS_READ_FILE | LANDLOCK_ACCESS_FS_READ_DIR |
+        LANDLOCK_ACCESS_FS_REMOVE_DIR | LANDLOCK_ACCESS_FS_REMOVE_FILE |
+        LANDLOCK_ACCESS_FS_MAKE_CHAR | LANDLOCK_ACCESS_FS_MAKE_DIR | LANDLOCK_ACCESS_FS_MAKE_REG |
+        LANDLOCK_ACCESS_FS_MAKE_SOCK | LANDLOCK_ACCESS_FS_MAKE_FIFO |
+        LANDLOCK_ACCESS_FS_MAKE_BLOCK | LANDLOCK_ACCESS_FS_MAKE_SYM;
+    if abi_version >= 2 {
+        access |= LANDLOCK_ACCESS_FS_REFER
+    }
+    if abi_version >= 3 {
+        access |= LANDLOCK_ACCESS_FS_TRUNCATE
+    }
+    if abi_version >= 5 {
+        access |= LANDLOCK_ACCESS_FS_IOCTL_DEV
+    }
+    access
+}
+
+/// Returns the network rights to restrict. A direction is left alone if the profile allows all
+/// addresses in it, and on kernels that predate network support, both are.
+fn handled_network_access(abi_version: u32, profile: &Profile) -> u64 {
+    if abi_version < NETWORK_ABI_VERSION {
+        return 0
+    }
+    let mut access = LANDLOCK_ACCESS_NET_BIND_TCP | LANDLOCK_ACCESS_NET_CONNECT_TCP;
+    for operation in profile.allowed_operations().iter() {
+        match *operation {
+            Operation::NetworkOutbound(AddressPattern::Tcp(_)) |
+            Operation::NetworkInbound(AddressPattern::Tcp(_)) => {}
+            Operation::NetworkOutbound(_) => access &= !LANDLOCK_ACCESS_NET_CONNECT_TCP,
+            Operation::NetworkInbound(_) => access &= !LANDLOCK_ACCESS_NET_BIND_TCP,
+            _ => {}
+        }
+    }
+    access
+}
+
+/// Returns the rights that `operation` grants, and the paths it grants them on.
+fn operation_access(operation: &Operation) -> (u64, Vec<PathBuf>) {
+    let create_and_unlink = LANDLOCK_ACCESS_FS_MAKE_REG | LANDLOCK_ACCESS_FS_MAKE_DIR |
+        LANDLOCK_ACCESS_FS_REMOVE_FILE | LANDLOCK_ACCESS_FS_REMOVE_DIR | LANDLOCK_ACCESS_FS_REFER;
+    match *operation {
+        Operation::FileReadAll(ref pattern) => {
+            (LANDLOCK_ACCESS_FS_READ_FILE | LANDLOCK_ACCESS_FS_READ_DIR,
+             namespace::pattern_paths(pattern))
+        }
+        Operation::FileWrite(ref pattern) => {
+            (LANDLOCK_ACCESS_FS_WRITE_FILE | LANDLOCK_ACCESS_FS_TRUNCATE,
+             namespace::pattern_paths(pattern))
+        }
+        Operation::FileCreateAndUnlink(ref pattern @ PathPattern::Subpath(_)) => {
+            (create_and_unlink, namespace::pattern_paths(pattern))
+        }
+        Operation::Exec(ref pattern) => {
+            (LANDLOCK_ACCESS_FS_EXECUTE | LANDLOCK_ACCESS_FS_READ_FILE,
+             namespace::pattern_paths(pattern))
+        }
+        Operation::DeviceAccess(device) => {
+            // The `ioctl`s themselves are narrowed down by the `seccomp` filter.
+            (LANDLOCK_ACCESS_FS_READ_FILE | LANDLOCK_ACCESS_FS_WRITE_FILE |
+                 LANDLOCK_ACCESS_FS_IOCTL_DEV,
+             vec![device.path().to_path_buf()])
+        }
+        Operation::ScratchDirectory { ref path, .. } => {
+            (create_and_unlink | LANDLOCK_ACCESS_FS_READ_FILE | LANDLOCK_ACCESS_FS_READ_DIR |
+                 LANDLOCK_ACCESS_FS_WRITE_FILE | LANDLOCK_ACCESS_FS_TRUNCATE |
+                 LANDLOCK_ACCESS_FS_MAKE_SYM | LANDLOCK_ACCESS_FS_MAKE_SOCK |
+                 LANDLOCK_ACCESS_FS_MAKE_FIFO,
+             vec![path.clone()])
+        }
+        _ => (0, vec![]),
+    }
+}
+
+/// Restricts this process's filesystem access to what the profile allows. This must be called
+/// from inside the `chroot` jail, since that's where the paths that the rules refer to are.
+///
+/// The same goes for TCP ports: bind and connect rights are restricted to the ports that the
+/// profile allows, if the kernel supports it.
+///
+/// If the kernel doesn't support Landlock, this does nothing, unless the profile relies on it
+/// (by allowing metadata reads or particular TCP ports), in which case it fails.
+pub fn activate(profile: &Profile) -> Result<(),Error> {
+    let abi_version = match abi_version() {
+        Some(abi_version) if abi_version >= NETWORK_ABI_VERSION ||
+                !requires_network_support(profile) => abi_version,
+        Some(_) => {
+            return Err(Error::Platform {
+                stage: Stage::CreateLandlockRuleset,
+                message: "Landlock can't restrict TCP ports on this kernel, but the profile \
+                          requires it".to_owned(),
+            })
+        }
+        None if requires_landlock(profile) => {
+            return Err(Error::Platform {
+                stage: Stage::CreateLandlockRuleset,
+                message: "Landlock is unavailable, but the profile requires it".to_owned(),
+            })
+        }
+        None => return Ok(()),
+    };
+    let handled_access = handled_access(abi_version);
+    let handled_network_access = handled_network_access(abi_version, profile);
+
+    let attributes = landlock_ruleset_attr {
+        handled_access_fs: handled_access,
+        handled_access_net: handled_network_access,
+    };
+    let attributes_size = if abi_version >= NETWORK_ABI_VERSION {
+        mem::size_of::<landlock_ruleset_attr>()
+    } else {
+        mem::size_of::<u64>()
+    };
+    let ruleset = unsafe {
+        libc::syscall(libc::SYS_landlock_create_ruleset,
+                      &attributes as *const landlock_ruleset_attr,
+                      attributes_size,
+                      0_u32)
+    };
+    if ruleset < 0 {
+        return Err(error::last_os_error(Stage::CreateLandlockRuleset, None))
+    }
+    let ruleset = ruleset as c_int;
+
+    let result = add_rules(ruleset, profile, handled_access)
+        .and_then(|_| add_network_rules(ruleset, profile, handled_network_access))
+        .and_then(|_| restrict_self(ruleset));
+    unsafe {
+        libc::close(ruleset);
+    }
+    result
+}
+
+/// Returns true if the profile can only be enforced with Landlock's help.
+pub fn requires_landlock(profile: &Profile) -> bool {
+    profile.allowed_operations().iter().any(|operation| {
+        matches!(*operation, Operation::FileReadMetadata(_))
+    }) || requires_network_support(profile)
+}
+
+/// Returns true if the profile allows particular TCP ports, which only Landlock can enforce.
+fn requires_network_support(profile: &Profile) -> bool {
+    profile.allowed_operations().iter().any(|operation| {
+        matches!(*operation,
+                 Operation::NetworkOutbound(AddressPattern::Tcp(_)) |
+                 Operation::NetworkInbound(AddressPattern::Tcp(_)))
+    })
+}
+
+fn add_rules(ruleset: c_int, profile: &Profile, handled_access: u64) -> Result<(),Error> {
+    for operation in profile.allowed_operations().iter() {
+        let (access, paths) = operation_access(operation);
+        for path in paths.iter() {
+            try!(add_rule(ruleset, path, access & handled_access));
+        }
+    }
+    Ok(())
+}
+
+/// Grants `access` on `path` and, if it is a directory, everything beneath it. Paths that don't
+/// exist are skipped, just as they are left out of the jail.
+fn add_rule(ruleset: c_int, path: &Path, access: u64) -> Result<(),Error> {
+    let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+    let fd = unsafe {
+        libc::open(c_path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC)
+    };
+    if fd < 0 {
+        return Ok(())
+    }
+
+    let mut stat: libc::stat = unsafe { mem::zeroed() };
+    let mut allowed_access = access;
+    if unsafe { libc::fstat(fd, &mut stat) } == 0 && stat.st_mode & libc::S_IFMT != libc::S_IFDIR {
+        allowed_access &= FILE_ACCESS
+    }
+    if allowed_access == 0 {
+        unsafe {
+            libc::close(fd);
+        }
+        return Ok(())
+    }
+
+    let attributes = landlock_path_beneath_attr {
+        allowed_access,
+        parent_fd: fd,
+    };
+    let result = unsafe {
+        libc::syscall(libc::SYS_landlock_add_rule,
+                      ruleset,
+                      LANDLOCK_RULE_PATH_BENEATH,
+                      &attributes as *const landlock_path_beneath_attr as *const c_void,
+                      0_u32)
+    };
+    let error = error::last_os_error(Stage::AddLandlockRule, Some(path));
+    unsafe {
+        libc::close(fd);
+    }
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(error)
+    }
+}
+
+fn add_network_rules(ruleset: c_int, profile: &Profile, handled_access: u64)
+                     -> Result<(),Error> {
+    for operation in profile.allowed_operations().iter() {
+        let (access, port) = match *operation {
+            Operation::NetworkOutbound(AddressPattern::Tcp(port)) => {
+                (LANDLOCK_ACCESS_NET_CONNECT_TCP, port)
+            }
+            Operation::NetworkInbound(AddressPattern::Tcp(port)) => {
+                (LANDLOCK_ACCESS_NET_BIND_TCP, port)
+            }
+            _ => continue,
+        };
+        if access & handled_access == 0 {
+            continue
+        }
+
+        let attributes = landlock_net_port_attr {
+            allowed_access: access,
+            port: port as u64,
+        };
+        let result = unsafe {
+            libc::syscall(libc::SYS_landlock_add_rule,
+                          ruleset,
+                          LANDLOCK_RULE_NET_PORT,
+                          &attributes as *const landlock_net_port_attr as *const c_void,
+                          0_u32)
+        };
+        if result != 0 {
+            return Err(error::last_os_error(Stage::AddLandlockRule, None))
+        }
+    }
+    Ok(())
+}
+
+fn restrict_self(ruleset: c_int) -> Result<(),Error> {
+    unsafe {
+        if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+            return Err(error::last_os_error(Stage::SetNoNewPrivileges, None))
+        }
+        if libc::syscall(libc::SYS_landlock_restrict_self, ruleset, 0_u32) != 0 {
+            return Err(error::last_os_error(Stage::EnforceLandlockRuleset, None))
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{abi_version, add_rule, LANDLOCK_ACCESS_FS_READ_FILE};
+    use error::{Error, Stage};
+
+    use libc;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn rule_errors_name_the_path() {
+        if abi_version().is_none() {
+            return
+        }
+        assert_eq!(add_rule(-1, Path::new("/"), LANDLOCK_ACCESS_FS_READ_FILE),
+                   Err(Error::Os {
+                       stage: Stage::AddLandlockRule,
+                       path: Some(PathBuf::from("/")),
+                       errno: libc::EBADF,
+                   }));
+    }
+}
@@ -10,26 +10,39 @@
 
 //! Sandboxing on Linux via miscellaneous kernel features.
 
+use error::{self, Error, Stage};
+use profile::{Operation, Profile};
+
 use libc;
 use std::io;
 
-pub fn activate() -> Result<(), libc::c_int> {
-    // Disable writing by setting the write limit to zero.
-    let rlimit = libc::rlimit {
-        rlim_cur: 0,
-        rlim_max: 0,
-    };
-    let result = unsafe {
-         libc::setrlimit(libc::RLIMIT_FSIZE, &rlimit)
-    };
-    if result != 0 {
-        return Err(result)
+/// Applies the miscellaneous restrictions. `broker_creates` says whether a broker may hand the
+/// process files to write to.
+pub fn activate(profile: &Profile, broker_creates: bool) -> Result<(),Error> {
+    // Disable writing by setting the write limit to zero, unless the profile allows files to be
+    // written or the broker may open them for writing.
+    if !broker_creates && !profile.allowed_operations().iter().any(|operation| {
+        matches!(*operation,
+                 Operation::FileWrite(_) |
+                 Operation::FileCreateAndUnlink(_) |
+                 Operation::ScratchDirectory { .. })
+    }) {
+        let rlimit = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        let result = unsafe {
+             libc::setrlimit(libc::RLIMIT_FSIZE, &rlimit)
+        };
+        if result != 0 {
+            return Err(error::last_os_error(Stage::SetResourceLimit, None))
+        }
     }
 
-    // Set a restrictive `umask` so that even if files happened to get written it'd be hard to do
-    // anything with them.
+    // Set a restrictive `umask`, so that the files and directories that the process creates are
+    // only accessible to its own user.
     unsafe {
-        libc::umask(0);
+        libc::umask(0o077);
     }
 
     // Disable core dumps and debugging via `PTRACE_ATTACH`.
@@ -37,27 +50,19 @@ pub fn activate() -> Result<(), libc::c_int> {
         libc::prctl(libc::PR_SET_DUMPABLE, 0, 0, 0, 0)
     };
     if result != 0 {
-        return Err(result)
+        return Err(error::last_os_error(Stage::SetDumpable, None))
     }
 
     // Enter a new session group. (This can fail with -EPERM if we're already the session leader,
     // which is OK.)
     unsafe {
         if libc::setsid() < 0 {
-            let result = io::Error::last_os_error().raw_os_error().unwrap() as i32;
-            if result != libc::EPERM {
-                return Err(result)
+            let error = io::Error::last_os_error();
+            if error.raw_os_error() != Some(libc::EPERM) {
+                return Err(error::io_error(Stage::CreateSession, None, error))
             }
         }
     }
 
-    // Clear out the process environment.
-    let result = unsafe {
-        libc::clearenv()
-    };
-    if result == 0 {
-        Ok(())
-    } else {
-        Err(result)
-    }
+    Ok(())
 }
@@ -8,35 +8,86 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use platform::linux::audit::AuditReport;
 use platform::linux::seccomp::Filter;
+use error::Error;
+use platform::unix::{environment, limits};
+use platform::unix::broker::BrokerClient;
 use platform::unix::process::Process;
-use profile::{self, AddressPattern, OperationSupport, OperationSupportLevel, Profile};
+use profile::{self, AddressPattern, OperationSupport, OperationSupportLevel, PathPattern, Profile};
 use sandbox::{ChildSandboxMethods, Command, SandboxMethods};
 
-use std::io;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+
+pub mod audit;
+pub mod landlock;
 pub mod misc;
 pub mod namespace;
 pub mod seccomp;
+pub mod supervisor;
+pub mod syscalls;
 
-#[allow(missing_copy_implementations)]
-#[derive(Clone, Debug)]
-pub struct Operation;
+/// Linux-specific operations.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Operation {
+    /// Paths matching this pattern that are mounted into the sandbox bring the filesystems
+    /// mounted beneath them along, with the same restrictions. Otherwise, only the filesystem
+    /// that such a path lives on is visible.
+    ///
+    /// Linux refuses to mount a path on its own if filesystems that were mounted outside the
+    /// sandbox lie beneath it (for example, `/dev`, with `/dev/pts` and `/dev/shm`), so such paths
+    /// need this operation.
+    RecursiveMount(PathPattern),
+}
 
 impl OperationSupport for profile::Operation {
     fn support(&self) -> OperationSupportLevel {
         match *self {
             profile::Operation::FileReadAll(_) |
-            profile::Operation::NetworkOutbound(AddressPattern::All) => {
+            profile::Operation::FileWrite(_) |
+            profile::Operation::FileCreateAndUnlink(PathPattern::Subpath(_)) |
+            profile::Operation::Exec(_) |
+            profile::Operation::SignalSelf |
+            profile::Operation::SignalChildren |
+            profile::Operation::DeviceAccess(_) |
+            profile::Operation::ScratchDirectory { .. } |
+            profile::Operation::NetworkOutbound(AddressPattern::All) |
+            profile::Operation::NetworkInbound(AddressPattern::All) => {
+                OperationSupportLevel::CanBeAllowed
+            }
+            profile::Operation::FileReadMetadata(_) if landlock::abi_version().is_some() => {
+                OperationSupportLevel::CanBeAllowed
+            }
+            profile::Operation::NetworkOutbound(AddressPattern::Tcp(_)) |
+            profile::Operation::NetworkInbound(AddressPattern::Tcp(_))
+                    if landlock::supports_network() => {
+                OperationSupportLevel::CanBeAllowed
+            }
+            profile::Operation::NetworkOutbound(AddressPattern::LocalSocket(_))
+                    if supervisor::supports_connecting() => {
                 OperationSupportLevel::CanBeAllowed
             }
             profile::Operation::FileReadMetadata(_) |
+            profile::Operation::FileCreateAndUnlink(PathPattern::Literal(_)) |
+            profile::Operation::FileCreateAndUnlink(PathPattern::Glob(_)) |
             profile::Operation::NetworkOutbound(AddressPattern::Tcp(_)) |
-            profile::Operation::NetworkOutbound(AddressPattern::LocalSocket(_)) => {
+            profile::Operation::NetworkOutbound(AddressPattern::LocalSocket(_)) |
+            profile::Operation::NetworkInbound(AddressPattern::Tcp(_)) |
+            profile::Operation::NetworkOutbound(AddressPattern::Ipv4Network(..)) |
+            profile::Operation::NetworkOutbound(AddressPattern::Ipv6Network(..)) |
+            profile::Operation::NetworkInbound(AddressPattern::LocalSocket(_)) |
+            profile::Operation::NetworkInbound(AddressPattern::Ipv4Network(..)) |
+            profile::Operation::NetworkInbound(AddressPattern::Ipv6Network(..)) => {
                 OperationSupportLevel::CannotBeAllowedPrecisely
             }
-            profile::Operation::SystemInfoRead |
-            profile::Operation::PlatformSpecific(_) => OperationSupportLevel::NeverAllowed,
+            profile::Operation::PlatformSpecific(Operation::RecursiveMount(_)) => {
+                OperationSupportLevel::CanBeAllowed
+            }
+            profile::Operation::SystemInfoRead => OperationSupportLevel::NeverAllowed,
         }
     }
 }
@@ -52,6 +103,14 @@ impl Sandbox {
         }
     }
 
+    /// Spawns a child process that runs its profile in audit mode, recording whatever it does
+    /// that the profile wouldn't allow in `report` instead of stopping it. The child must
+    /// activate its sandbox with `ChildSandbox::audit`. See the `audit` module for details.
+    pub fn start_audited(&self, command: &mut Command, report: AuditReport)
+                         -> Result<Process,Error> {
+        audit::start(&self.profile, command, report)
+    }
+
     #[cfg(dump_bpf_sockets)]
     fn dump_filter(&self) {
         let filter = Filter::new(&self.profile);
@@ -67,7 +126,7 @@ impl SandboxMethods for Sandbox {
         &self.profile
     }
 
-    fn start(&self, command: &mut Command) -> io::Result<Process> {
+    fn start(&self, command: &mut Command) -> Result<Process,Error> {
         self.dump_filter();
         namespace::start(&self.profile, command)
     }
@@ -75,28 +134,61 @@ impl SandboxMethods for Sandbox {
 
 pub struct ChildSandbox {
     profile: Profile,
+    broker: Option<BrokerClient>,
+    /// Whether this process reports violations to its supervisor rather than being stopped by
+    /// them.
+    audit: bool,
 }
 
 impl ChildSandbox {
     pub fn new(profile: Profile) -> ChildSandbox {
         ChildSandbox {
             profile: profile,
+            broker: BrokerClient::from_environment(),
+            audit: false,
+        }
+    }
+
+    /// Creates a sandbox that runs `profile` in audit mode, for a process started with
+    /// `Sandbox::start_audited`. Activating it enforces nothing but the profile's resource limits
+    /// and environment.
+    pub fn audit(profile: Profile) -> ChildSandbox {
+        ChildSandbox {
+            audit: true,
+            ..ChildSandbox::new(profile)
         }
     }
 }
 
 impl ChildSandboxMethods for ChildSandbox {
-    fn activate(&self) -> Result<(),()> {
-        if namespace::activate(&self.profile).is_err() {
-            return Err(())
+    fn activate(&self) -> Result<(),Error> {
+        // Look for the supervisor before the environment variable naming it is cleared.
+        let supervised = self.audit || supervisor::requires_supervisor(&self.profile);
+        let supervisor_channel = try!(supervisor::sandbox_channel(supervised));
+        try!(limits::activate(self.profile.resource_limits()));
+        try!(environment::activate(self.profile.environment()));
+        if self.audit {
+            // Leave the filesystem as it is, so that the supervisor sees every path the process
+            // uses.
+            let listener = try!(Filter::audit(&self.profile).activate_with_listener());
+            return supervisor::hand_over_listener(supervisor_channel.unwrap(), listener)
         }
-        if misc::activate().is_err() {
-            return Err(())
-        }
-        match Filter::new(&self.profile).activate() {
-            Ok(_) => Ok(()),
-            Err(_) => Err(()),
+        try!(namespace::activate(&self.profile));
+        let broker_creates = self.broker.as_ref().is_some_and(|broker| broker.may_create());
+        try!(misc::activate(&self.profile, broker_creates));
+        try!(landlock::activate(&self.profile));
+        let filter = Filter::new(&self.profile);
+        match supervisor_channel {
+            Some(channel) => {
+                let listener = try!(filter.activate_with_listener());
+                supervisor::hand_over_listener(channel, listener)
+            }
+            None => filter.activate(),
         }
     }
+
+    fn broker(&self) -> Option<&BrokerClient> {
+        self.broker.as_ref()
+    }
 }
 
@@ -10,25 +10,28 @@
 
 //! Sandboxing on Linux via namespaces.
 
-use platform::linux::seccomp;
+use error::{self, Error, Stage};
+use glob;
+use platform::linux::{self, supervisor};
 use platform::unix::process::Process;
 use platform::unix;
-use profile::{Operation, PathPattern, Profile};
+use profile::{AddressPattern, Operation, PathPattern, Profile};
 use sandbox::Command;
 
 use libc::{self, c_char, c_int, c_ulong, c_void, gid_t, pid_t, size_t, ssize_t, uid_t};
 use std::env;
 use std::ffi::{CString, OsStr, OsString};
 use std::fs::{self, File};
-use std::io::{self, Write};
+use std::io::{self, Read, Write};
 use std::iter;
 use std::mem;
-use std::os::unix::prelude::OsStrExt;
+use std::os::unix::prelude::{OsStrExt, OsStringExt};
 use std::path::{Path, PathBuf};
 use std::ptr;
+use std::str;
 
 /// Creates a namespace and sets up a chroot jail.
-pub fn activate(profile: &Profile) -> Result<(),c_int> {
+pub fn activate(profile: &Profile) -> Result<(),Error> {
     let jail = try!(ChrootJail::new(profile));
     try!(jail.enter());
     drop_capabilities()
@@ -37,21 +40,46 @@ pub fn activate(profile: &Profile) -> Result<(),c_int> {
 /// A `chroot` jail with a restricted view of the filesystem inside it.
 struct ChrootJail {
     directory: PathBuf,
+    /// Bind mounts of paths matching these patterns include the mounts beneath them.
+    recursive_patterns: Vec<PathPattern>,
 }
 
 impl ChrootJail {
     /// Creates a new `chroot` jail.
-    fn new(profile: &Profile) -> Result<ChrootJail,c_int> {
+    fn new(profile: &Profile) -> Result<ChrootJail,Error> {
+        // Stop mount events from propagating between our mount namespace and the one we came
+        // from, in either direction, before we start building the jail.
+        let root = CString::new("/").unwrap();
+        let result = unsafe {
+            libc::mount(ptr::null(),
+                        root.as_ptr(),
+                        ptr::null(),
+                        libc::MS_REC | libc::MS_PRIVATE,
+                        ptr::null())
+        };
+        if result != 0 {
+            return Err(error::last_os_error(Stage::MakeMountsPrivate, None))
+        }
+
         let prefix = CString::new("/tmp/gaol.XXXXXX").unwrap();
         let mut prefix: Vec<u8> = prefix.as_bytes_with_nul().iter().map(|x| *x).collect();
         unsafe {
             if libc::mkdtemp(prefix.as_mut_ptr() as *mut c_char).is_null() {
-                return Err(-1)
+                return Err(error::last_os_error(Stage::CreateJailDirectory,
+                                                Some(Path::new("/tmp"))))
             }
         }
         let jail_dir = PathBuf::from(OsStr::from_bytes(&prefix[..prefix.len() - 1]));
         let jail = ChrootJail {
             directory: jail_dir,
+            recursive_patterns: profile.allowed_operations().iter().filter_map(|operation| {
+                match *operation {
+                    Operation::PlatformSpecific(linux::Operation::RecursiveMount(ref pattern)) => {
+                        Some(pattern.clone())
+                    }
+                    _ => None,
+                }
+            }).collect(),
         };
 
         let dest = CString::new(jail.directory
@@ -60,32 +88,93 @@ impl ChrootJail {
                                     .unwrap()
                                     .as_bytes()).unwrap();
         let tmpfs = CString::new("tmpfs").unwrap();
+        let jail_flags = libc::MS_NOATIME | libc::MS_NODEV | libc::MS_NOEXEC | libc::MS_NOSUID;
         let result = unsafe {
-            libc::mount(tmpfs.as_ptr(),
-                        dest.as_ptr(),
-                        tmpfs.as_ptr(),
-                        libc::MS_NOATIME | libc::MS_NODEV | libc::MS_NOEXEC | libc::MS_NOSUID,
-                        ptr::null())
+            libc::mount(tmpfs.as_ptr(), dest.as_ptr(), tmpfs.as_ptr(), jail_flags, ptr::null())
         };
         if result != 0 {
-            return Err(result)
+            return Err(error::last_os_error(Stage::MountJailFilesystem, Some(&jail.directory)))
         }
 
+        // If programs may be executed, make sure that only the ones we were asked to allow are
+        // executable.
+        let noexec = if profile.allowed_operations().iter().any(|operation| {
+            match *operation {
+                Operation::Exec(_) => true,
+                _ => false,
+            }
+        }) {
+            libc::MS_NOEXEC
+        } else {
+            0
+        };
+
+        // Nothing in the jail may be a device node or grant privileges, except for the devices
+        // that the profile asks for.
+        let hardened = libc::MS_NODEV | libc::MS_NOSUID;
+
+        // Mount read-only paths first so that writable and executable mounts of overlapping paths
+        // end up on top.
+        // Paths whose metadata may be read are mounted like paths that may be read in full;
+        // Landlock then keeps their contents from being read.
+        for operation in profile.allowed_operations().iter() {
+            match *operation {
+                Operation::FileReadAll(ref pattern) | Operation::FileReadMetadata(ref pattern) => {
+                    try!(jail.bind_mount_pattern(pattern, libc::MS_RDONLY | hardened | noexec));
+                }
+                _ => {}
+            }
+        }
         for operation in profile.allowed_operations().iter() {
             match *operation {
-                Operation::FileReadAll(PathPattern::Literal(ref path)) |
-                Operation::FileReadAll(PathPattern::Subpath(ref path)) => {
-                    try!(jail.bind_mount(path));
+                // `Profile::new()` rejects other patterns for creating and unlinking files,
+                // since only whole directories can be made writable.
+                Operation::FileWrite(ref pattern) |
+                Operation::FileCreateAndUnlink(ref pattern @ PathPattern::Subpath(_)) => {
+                    try!(jail.bind_mount_pattern(pattern, hardened | noexec));
                 }
                 _ => {}
             }
         }
+        // Device nodes can't be created inside a user namespace, so bind mount the host's. The
+        // bind mount is a mount of its own, so the `MS_NODEV` flag of the jail's `tmpfs` doesn't
+        // apply to it.
+        for operation in profile.allowed_operations().iter() {
+            if let Operation::DeviceAccess(device) = *operation {
+                try!(jail.bind_mount(device.path(), libc::MS_NOSUID | noexec));
+            }
+        }
+        // Scratch directories go on top of the other file mounts, since they're supposed to start
+        // out empty.
+        for operation in profile.allowed_operations().iter() {
+            if let Operation::ScratchDirectory { ref path, max_bytes } = *operation {
+                try!(jail.mount_scratch_directory(path, max_bytes));
+            }
+        }
+        for operation in profile.allowed_operations().iter() {
+            if let Operation::Exec(ref pattern) = *operation {
+                try!(jail.bind_mount_pattern(pattern, libc::MS_RDONLY | hardened));
+            }
+        }
+
+        // Now that every mount point exists, make the jail's own `tmpfs` read-only, so that
+        // nothing can be created in it outside of the mounts.
+        let result = unsafe {
+            libc::mount(ptr::null(),
+                        dest.as_ptr(),
+                        ptr::null(),
+                        libc::MS_REMOUNT | libc::MS_RDONLY | jail_flags,
+                        ptr::null())
+        };
+        if result != 0 {
+            return Err(error::last_os_error(Stage::MountJailFilesystem, Some(&jail.directory)))
+        }
 
         Ok(jail)
     }
 
     /// Enters the `chroot` jail.
-    fn enter(&self) -> Result<(),c_int> {
+    fn enter(&self) -> Result<(),Error> {
         let directory = CString::new(self.directory
                                          .as_os_str()
                                          .to_str()
@@ -95,43 +184,99 @@ impl ChrootJail {
             libc::chroot(directory.as_ptr())
         };
         if result != 0 {
-            return Err(result)
+            return Err(error::last_os_error(Stage::EnterJail, Some(&self.directory)))
         }
 
-        match env::set_current_dir(&Path::new(".")) {
-            Ok(_) => Ok(()),
-            Err(_) => Err(-1),
+        env::set_current_dir(Path::new(".")).map_err(|error| {
+            error::io_error(Stage::EnterJail, Some(&self.directory), error)
+        })
+    }
+
+    /// Creates the directory at `path` inside our chroot jail, along with any missing parents,
+    /// and mounts a fresh `tmpfs` of at most `max_bytes` bytes there.
+    fn mount_scratch_directory(&self, path: &Path, max_bytes: u64) -> Result<(),Error> {
+        let components = path.components().skip(1)
+                                           .map(|component| component.as_os_str().to_os_string())
+                                           .collect();
+        let destination_path = try!(self.create_directories(components,
+                                                            Stage::CreateScratchDirectory));
+        let destination = CString::new(destination_path.as_os_str()
+                                                       .to_str()
+                                                       .unwrap()
+                                                       .as_bytes()).unwrap();
+        let tmpfs = CString::new("tmpfs").unwrap();
+        let options = CString::new(format!("size={},mode=0700", max_bytes)).unwrap();
+        let result = unsafe {
+            libc::mount(tmpfs.as_ptr(),
+                        destination.as_ptr(),
+                        tmpfs.as_ptr(),
+                        libc::MS_NOATIME | libc::MS_NODEV | libc::MS_NOEXEC | libc::MS_NOSUID,
+                        options.as_ptr() as *const c_void)
+        };
+        if result != 0 {
+            return Err(error::last_os_error(Stage::CreateScratchDirectory, Some(path)))
         }
+        Ok(())
     }
 
-    /// Bind mounts a path into our chroot jail.
-    fn bind_mount(&self, source_path: &Path) -> Result<(),c_int> {
-        // Create all intermediate directories.
+    /// Creates the directories named by `components` inside our chroot jail, each inside the
+    /// previous one, reusing any that already exist. Returns the path of the innermost one.
+    fn create_directories(&self, components: Vec<OsString>, stage: Stage)
+                          -> Result<PathBuf,Error> {
         let mut destination_path = self.directory.clone();
+        for component in components.into_iter() {
+            destination_path.push(component);
+            if !destination_path.is_dir() {
+                try!(fs::create_dir(&destination_path).map_err(|error| {
+                    error::io_error(stage, Some(&destination_path), error)
+                }))
+            }
+        }
+        Ok(destination_path)
+    }
+
+    /// Bind mounts every path described by a pattern into our chroot jail.
+    fn bind_mount_pattern(&self, pattern: &PathPattern, flags: c_ulong) -> Result<(),Error> {
+        for path in pattern_paths(pattern).iter() {
+            try!(self.bind_mount(path, flags));
+        }
+        Ok(())
+    }
+
+    /// Bind mounts a path into our chroot jail. The mount is then remounted with the given extra
+    /// flags (for example, `MS_RDONLY`), if any.
+    ///
+    /// Only the filesystem that the path lives on is mounted, unless the profile asks for the path
+    /// to be mounted recursively, in which case the mounts beneath it are remounted with the same
+    /// flags.
+    fn bind_mount(&self, source_path: &Path, flags: c_ulong) -> Result<(),Error> {
+        // Create all intermediate directories, reusing any that an earlier mount already created.
         let mut components: Vec<OsString> =
             source_path.components().skip(1)
                                     .map(|component| component.as_os_str().to_os_string())
                                     .collect();
         let last_component = components.pop();
-        for component in components.into_iter() {
-            destination_path.push(component);
-            if fs::create_dir(&destination_path).is_err() {
-                return Err(-1)
-            }
-        }
+        let mut destination_path = try!(self.create_directories(components,
+                                                                Stage::CreateMountPoint));
 
         // Create the mount file or directory.
         if let Some(last_component) = last_component {
             destination_path.push(last_component);
             match fs::metadata(source_path) {
                 Ok(ref metadata) if metadata.is_dir() => {
-                    if fs::create_dir(&destination_path).is_err() {
-                        return Err(-1)
+                    if !destination_path.is_dir() {
+                        try!(fs::create_dir(&destination_path).map_err(|error| {
+                            error::io_error(Stage::CreateMountPoint, Some(&destination_path), error)
+                        }))
                     }
                 }
                 Ok(_) => {
-                    if File::create(&destination_path).is_err() {
-                        return Err(-1)
+                    // Don't use `File::create` here: if the file is already visible through
+                    // another bind mount, that would truncate the original.
+                    if fs::symlink_metadata(&destination_path).is_err() {
+                        try!(File::create(&destination_path).map_err(|error| {
+                            error::io_error(Stage::CreateMountPoint, Some(&destination_path), error)
+                        }));
                     }
                 }
                 Err(_) => {
@@ -142,11 +287,14 @@ impl ChrootJail {
         }
 
         // Create the bind mount.
+        let recursive = self.recursive_patterns.iter().any(|pattern| pattern.matches(source_path));
+        let destination = destination_path;
+        let source = source_path;
         let source_path = CString::new(source_path.as_os_str()
                                                   .to_str()
                                                   .unwrap()
                                                   .as_bytes()).unwrap();
-        let destination_path = CString::new(destination_path.as_os_str()
+        let destination_path = CString::new(destination.as_os_str()
                                                             .to_str()
                                                             .unwrap()
                                                             .as_bytes()).unwrap();
@@ -155,20 +303,134 @@ impl ChrootJail {
             libc::mount(source_path.as_ptr(),
                   destination_path.as_ptr(),
                   bind.as_ptr(),
-                  libc::MS_MGC_VAL | libc::MS_BIND | libc::MS_REC,
+                  libc::MS_MGC_VAL | libc::MS_BIND | if recursive { libc::MS_REC } else { 0 },
                   ptr::null_mut())
         };
-        if result == 0 {
-            Ok(())
-        } else {
-            Err(result)
+        if result != 0 {
+            return Err(error::last_os_error(Stage::BindMount, Some(source)))
+        }
+
+        // The kernel ignores flags other than `MS_BIND` and `MS_REC` when creating a bind mount,
+        // so apply them with a remount.
+        if flags == 0 {
+            return Ok(())
+        }
+        let mut mount_points = vec![destination_path];
+        if recursive {
+            mount_points.extend(nested_mount_points(&destination).into_iter().map(|path| {
+                CString::new(path.into_os_string().into_vec()).unwrap()
+            }))
+        }
+        for mount_point in mount_points.iter() {
+            try!(remount(mount_point, flags).map_err(|errno| {
+                Error::Os {
+                    stage: Stage::Remount,
+                    path: Some(source.to_path_buf()),
+                    errno,
+                }
+            }))
+        }
+        Ok(())
+    }
+}
+
+/// Returns the mount points strictly beneath `directory`, according to `/proc/self/mountinfo`.
+fn nested_mount_points(directory: &Path) -> Vec<PathBuf> {
+    let mut mount_info = String::new();
+    if File::open("/proc/self/mountinfo").and_then(|mut file| {
+        file.read_to_string(&mut mount_info)
+    }).is_err() {
+        return vec![]
+    }
+    mount_info.lines().filter_map(|line| line.split(' ').nth(4)).map(|mount_point| {
+        PathBuf::from(OsString::from_vec(unescape_mount_point(mount_point)))
+    }).filter(|mount_point| {
+        mount_point != directory && mount_point.starts_with(directory)
+    }).collect()
+}
+
+/// Decodes the octal escapes (such as `\040` for a space) in a mount point listed in
+/// `/proc/self/mountinfo`.
+fn unescape_mount_point(mount_point: &str) -> Vec<u8> {
+    let bytes = mount_point.as_bytes();
+    let mut result = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'\\' && index + 4 <= bytes.len() {
+            let digits = str::from_utf8(&bytes[index + 1..index + 4]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(digits, 8) {
+                result.push(byte);
+                index += 4;
+                continue
+            }
+        }
+        result.push(bytes[index]);
+        index += 1
+    }
+    result
+}
+
+/// Returns the paths that need to be mounted into the jail for `pattern`. Globs are expanded
+/// against the filesystem as it is right now. A glob only describes the paths that match it, and
+/// mounting a directory would expose everything beneath it too, so directories are left out.
+pub fn pattern_paths(pattern: &PathPattern) -> Vec<PathBuf> {
+    match *pattern {
+        PathPattern::Literal(ref path) | PathPattern::Subpath(ref path) => vec![path.clone()],
+        PathPattern::Glob(ref pattern) => {
+            glob::expand(pattern).into_iter().filter(|path| !path.is_dir()).collect()
+        }
+    }
+}
+
+/// Remounts the bind mount at `destination_path`, adding `flags`. On failure, returns the `errno`
+/// value.
+///
+/// Inside a user namespace, the kernel refuses to clear "locked" flags (such as `MS_NOSUID`)
+/// inherited from the original mount, so we have to carry over the flags the mount already has.
+fn remount(destination_path: &CString, flags: c_ulong) -> Result<(),c_int> {
+    let mut existing_flags = 0;
+    unsafe {
+        let mut stat: libc::statvfs = mem::zeroed();
+        if libc::statvfs(destination_path.as_ptr(), &mut stat) != 0 {
+            return Err(errno())
         }
+        for &(statvfs_flag, mount_flag) in [
+            (libc::ST_RDONLY, libc::MS_RDONLY),
+            (libc::ST_NOSUID, libc::MS_NOSUID),
+            (libc::ST_NODEV, libc::MS_NODEV),
+            (libc::ST_NOEXEC, libc::MS_NOEXEC),
+            (libc::ST_NOATIME, libc::MS_NOATIME),
+            (libc::ST_NODIRATIME, libc::MS_NODIRATIME),
+            (libc::ST_RELATIME, libc::MS_RELATIME),
+        ].iter() {
+            if stat.f_flag & statvfs_flag != 0 {
+                existing_flags |= mount_flag
+            }
+        }
+    }
+
+    let result = unsafe {
+        libc::mount(ptr::null(),
+                    destination_path.as_ptr(),
+                    ptr::null(),
+                    libc::MS_BIND | libc::MS_REMOUNT | existing_flags | flags,
+                    ptr::null())
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(errno())
     }
 }
 
+/// Returns the calling thread's `errno` value.
+fn errno() -> c_int {
+    io::Error::last_os_error().raw_os_error().unwrap_or(libc::EIO)
+}
+
 /// Removes fake-superuser capabilities. This removes our ability to mess with the filesystem view
 /// we've set up.
-fn drop_capabilities() -> Result<(),c_int> {
+fn drop_capabilities() -> Result<(),Error> {
     let capability_data: Vec<_> = iter::repeat(__user_cap_data_struct {
         effective: 0,
         permitted: 0,
@@ -183,7 +445,7 @@ fn drop_capabilities() -> Result<(),c_int> {
     if result == 0 {
         Ok(())
     } else {
-        Err(result)
+        Err(error::last_os_error(Stage::DropCapabilities, None))
     }
 }
 
@@ -205,7 +467,7 @@ unsafe fn prepare_user_and_pid_namespaces(parent_uid: uid_t, parent_gid: gid_t)
 /// Spawns a child process in a new namespace.
 ///
 /// This function is quite tricky. Hic sunt dracones!
-pub fn start(profile: &Profile, command: &mut Command) -> io::Result<Process> {
+pub fn start(profile: &Profile, command: &mut Command) -> Result<Process,Error> {
     // Store our root namespace UID and GID because they're going to change once we enter a user
     // namespace.
     let (parent_uid, parent_gid) = unsafe {
@@ -213,28 +475,54 @@ pub fn start(profile: &Profile, command: &mut Command) -> io::Result<Process> {
     };
 
     // Always create an IPC namespace, a mount namespace, and a UTS namespace. Additionally, if we
-    // aren't allowing network operations, create a network namespace.
+    // aren't allowing network operations, create a network namespace. (Inbound connections need
+    // the parent's network namespace too, so that the listening port is reachable from outside.)
+    // Unix sockets are reached through the filesystem, so connecting to them doesn't need the
+    // parent's network namespace.
     let mut unshare_flags = libc::CLONE_NEWIPC | libc::CLONE_NEWNS | libc::CLONE_NEWUTS;
     if !profile.allowed_operations().iter().any(|operation| {
         match *operation {
-            Operation::NetworkOutbound(_) => true,
+            Operation::NetworkOutbound(AddressPattern::LocalSocket(_)) => false,
+            Operation::NetworkOutbound(_) | Operation::NetworkInbound(_) => true,
             _ => false,
         }
     }) {
         unshare_flags |= libc::CLONE_NEWNET
     }
 
+    // If the sandboxed process will need a supervisor, set up the channel through which it will
+    // reach us.
+    let supervisor_channel = if supervisor::requires_supervisor(profile) {
+        Some(try!(supervisor::Channel::new()))
+    } else {
+        None
+    };
+
     unsafe {
         // Create a pipe so we can communicate the PID of our grandchild back.
         let mut pipe_fds = [0, 0];
-        assert!(libc::pipe(&mut pipe_fds[0]) == 0);
+        if libc::pipe(&mut pipe_fds[0]) != 0 {
+            return Err(error::last_os_error(Stage::Spawn, None))
+        }
 
         // Set this `prctl` flag so that we can wait on our grandchild. (Otherwise it'll be
         // reparented to init.)
-        assert!(libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0) == 0);
+        if libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0) != 0 {
+            let error = error::last_os_error(Stage::Spawn, None);
+            libc::close(pipe_fds[0]);
+            libc::close(pipe_fds[1]);
+            return Err(error)
+        }
 
         // Fork so that we can unshare without removing our ability to create threads.
-        if libc::fork() == 0 {
+        let child_pid = libc::fork();
+        if child_pid < 0 {
+            let error = error::last_os_error(Stage::Spawn, None);
+            libc::close(pipe_fds[0]);
+            libc::close(pipe_fds[1]);
+            return Err(error)
+        }
+        if child_pid == 0 {
             // Close the reading end of the pipe.
             libc::close(pipe_fds[0]);
 
@@ -248,6 +536,13 @@ pub fn start(profile: &Profile, command: &mut Command) -> io::Result<Process> {
                     // Enter the auxiliary namespaces.
                     assert!(libc::unshare(unshare_flags) == 0);
 
+                    // Only the grandchild's copy of the command learns where the channel is.
+                    if let Some(ref channel) = supervisor_channel {
+                        channel.inherit();
+                        let (name, value) = channel.environment_variable();
+                        command.env(name, value);
+                    }
+
                     // Go ahead and start the command.
                     drop(unix::process::exec(command));
                     libc::abort()
@@ -266,14 +561,32 @@ pub fn start(profile: &Profile, command: &mut Command) -> io::Result<Process> {
         // Grandparent execution continues here. First, close the writing end of the pipe.
         libc::close(pipe_fds[1]);
 
-        // Retrieve our grandchild's PID.
+        // Retrieve our grandchild's PID. If our child failed before sending it, the pipe is simply
+        // closed.
         let mut grandchild_pid: pid_t = 0;
-        assert!(libc::read(pipe_fds[0],
-                           &mut grandchild_pid as *mut i32 as *mut c_void,
-                           mem::size_of::<pid_t>() as size_t) ==
-                mem::size_of::<pid_t>() as ssize_t);
+        let result = libc::read(pipe_fds[0],
+                                &mut grandchild_pid as *mut i32 as *mut c_void,
+                                mem::size_of::<pid_t>() as size_t);
+        let error = error::last_os_error(Stage::Spawn, None);
+        libc::close(pipe_fds[0]);
+        if result != mem::size_of::<pid_t>() as ssize_t {
+            return Err(if result < 0 {
+                error
+            } else {
+                Error::Os {
+                    stage: Stage::Spawn,
+                    path: None,
+                    errno: libc::ECHILD,
+                }
+            })
+        }
+        if let Some(channel) = supervisor_channel {
+            channel.supervise(profile.clone())
+        }
         Ok(Process {
             pid: grandchild_pid,
+            cpu_time_limit: profile.resource_limits().cpu_seconds,
+            scratch_directories: vec![],
         })
     }
 }
@@ -304,3 +617,50 @@ const _LINUX_CAPABILITY_U32S_3: u32 = 2;
 extern {
     fn capset(hdrp: cap_user_header_t, datap: const_cap_user_data_t) -> c_int;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ChrootJail;
+    use error::{Error, Stage};
+
+    use libc;
+    use std::path::{Path, PathBuf};
+
+    /// A jail whose directory doesn't exist, so that building it fails without touching anything.
+    fn missing_jail() -> ChrootJail {
+        ChrootJail {
+            directory: PathBuf::from("/nonexistent/gaol-jail"),
+            recursive_patterns: vec![],
+        }
+    }
+
+    #[test]
+    fn mount_point_errors_name_the_mount_point() {
+        assert_eq!(missing_jail().bind_mount(Path::new("/etc/passwd"), 0),
+                   Err(Error::Os {
+                       stage: Stage::CreateMountPoint,
+                       path: Some(PathBuf::from("/nonexistent/gaol-jail/etc")),
+                       errno: libc::ENOENT,
+                   }));
+    }
+
+    #[test]
+    fn scratch_directory_errors_name_the_directory() {
+        assert_eq!(missing_jail().mount_scratch_directory(Path::new("/scratch"), 1024),
+                   Err(Error::Os {
+                       stage: Stage::CreateScratchDirectory,
+                       path: Some(PathBuf::from("/nonexistent/gaol-jail/scratch")),
+                       errno: libc::ENOENT,
+                   }));
+    }
+
+    #[test]
+    fn chroot_errors_name_the_jail() {
+        assert_eq!(missing_jail().enter(),
+                   Err(Error::Os {
+                       stage: Stage::EnterJail,
+                       path: Some(PathBuf::from("/nonexistent/gaol-jail")),
+                       errno: libc::ENOENT,
+                   }));
+    }
+}
@@ -16,39 +16,34 @@
 
 #![allow(non_upper_case_globals, unused_imports)]
 
-use profile::{Operation, Profile};
+use error::{self, Error, Stage};
+use platform::linux::supervisor::{self, SECCOMP_RET_USER_NOTIF};
+use platform::linux::syscalls::{self, Architecture, Syscall};
+use profile::{AddressPattern, Operation, Profile, ViolationAction};
 
 use libc::{self, CLONE_CHILD_CLEARTID, CLONE_FILES, CLONE_FS,
            CLONE_PARENT_SETTID, CLONE_SETTLS, CLONE_SIGHAND, CLONE_SYSVSEM,
            CLONE_THREAD, CLONE_VM};
+use libc::{CLONE_NEWCGROUP, CLONE_NEWIPC, CLONE_NEWNET, CLONE_NEWNS, CLONE_NEWPID,
+           CLONE_NEWUSER, CLONE_NEWUTS};
 use libc::{AF_INET, AF_INET6, AF_UNIX, AF_NETLINK};
-use libc::{c_char, c_int, c_ulong, c_ushort, c_void};
-use libc::{O_NONBLOCK, O_RDONLY, O_NOCTTY, O_CLOEXEC, FIONREAD, FIOCLEX};
+use libc::{IPPROTO_IPV6, IPPROTO_TCP, SOCK_CLOEXEC, SOCK_NONBLOCK, SOCK_STREAM, SOL_SOCKET};
+use libc::{c_char, c_int, c_uint, c_ulong, c_ushort, c_void};
+use libc::{O_NONBLOCK, O_RDONLY, O_NOCTTY, O_CLOEXEC, O_DIRECTORY, FIONREAD, FIOCLEX};
+use libc::{F_DUPFD_CLOEXEC, F_GETFD, F_GETFL, F_SETFD};
+use libc::{O_APPEND, O_CREAT, O_EXCL, O_LARGEFILE, O_RDWR, O_TRUNC, O_WRONLY};
 use libc::{MADV_NORMAL, MADV_RANDOM, MADV_SEQUENTIAL, MADV_WILLNEED, MADV_DONTNEED};
 use std::ffi::CString;
 use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-/// The architecture number for x86.
-#[cfg(target_arch="x86")]
-const ARCH_NR: u32 = AUDIT_ARCH_X86;
-/// The architecture number for x86-64.
-#[cfg(target_arch="x86_64")]
-const ARCH_NR: u32 = AUDIT_ARCH_X86_64;
-/// The architecture number for ARM.
-#[cfg(target_arch="arm")]
-const ARCH_NR: u32 = AUDIT_ARCH_ARM;
-/// The architecture number for ARM 64-bit.
-#[cfg(target_arch="aarch64")]
-const ARCH_NR: u32 = AUDIT_ARCH_AARCH64;
-#[cfg(target_arch="powerpc")]
-const ARCH_NR: u32 = AUDIT_ARCH_PPC;
-#[cfg(all(target_arch="powerpc64", target_endian="big"))]
-const ARCH_NR: u32 = AUDIT_ARCH_PPC64;
-#[cfg(all(target_arch="powerpc64", target_endian="little"))]
-const ARCH_NR: u32 = AUDIT_ARCH_PPC64LE;
-
-const SECCOMP_RET_KILL: u32 = 0;
+const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+const SECCOMP_RET_KILL_THREAD: u32 = 0;
+const SECCOMP_RET_TRAP: u32 = 0x0003_0000;
+const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
 const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+const SECCOMP_RET_DATA: u32 = 0x0000_ffff;
 
 const LD: u16 = 0x00;
 const JMP: u16 = 0x05;
@@ -65,90 +60,156 @@ const K: u16 = 0x00;
 const SYSCALL_NR_OFFSET: u32 = 0;
 const ARCH_NR_OFFSET: u32 = 4;
 const ARG_0_OFFSET: u32 = 16;
-const ARG_1_OFFSET: u32 = 24;
-const ARG_2_OFFSET: u32 = 32;
+/// Arguments are 64 bits wide, stored in the byte order of the architecture.
+const ARG_SIZE: u32 = 8;
 
 const NETLINK_ROUTE: c_int = 0;
 
-const EM_386: u32 = 3;
-const EM_PPC: u32 = 20;
-const EM_PPC64: u32 = 21;
-const EM_ARM: u32 = 40;
-const EM_X86_64: u32 = 62;
-const EM_AARCH64: u32 = 183;
-
-/// A flag set in the architecture number for all 64-bit architectures.
-const __AUDIT_ARCH_64BIT: u32 = 0x8000_0000;
-/// A flag set in the architecture number for all little-endian architectures.
-const __AUDIT_ARCH_LE: u32 = 0x4000_0000;
-/// The architecture number for x86.
-const AUDIT_ARCH_X86: u32 = EM_386 | __AUDIT_ARCH_LE;
-/// The architecture number for x86-64.
-const AUDIT_ARCH_X86_64: u32 = EM_X86_64 | __AUDIT_ARCH_64BIT | __AUDIT_ARCH_LE;
-/// The architecture number for ARM.
-const AUDIT_ARCH_ARM: u32 = EM_ARM | __AUDIT_ARCH_LE;
-/// The architecture number for ARM 64-bit.
-const AUDIT_ARCH_AARCH64: u32 = EM_AARCH64 | __AUDIT_ARCH_64BIT | __AUDIT_ARCH_LE;
-/// The architecture number for ppc.
-const AUDIT_ARCH_PPC: u32 = EM_PPC;
-/// The architecture number for ppc64.
-const AUDIT_ARCH_PPC64: u32 = EM_PPC64 | __AUDIT_ARCH_64BIT;
-/// The architecture number for ppc64le.
-const AUDIT_ARCH_PPC64LE: u32 = EM_PPC64 | __AUDIT_ARCH_64BIT | __AUDIT_ARCH_LE;
-
 const PR_SET_SECCOMP: c_int = 22;
 const PR_SET_NO_NEW_PRIVS: c_int = 38;
 
 const SECCOMP_MODE_FILTER: c_ulong = 2;
 
-static FILTER_PROLOGUE: [sock_filter; 3] = [
-    VALIDATE_ARCHITECTURE_0,
-    VALIDATE_ARCHITECTURE_1,
-    VALIDATE_ARCHITECTURE_2,
+const SECCOMP_SET_MODE_FILTER: c_uint = 1;
+const SECCOMP_FILTER_FLAG_NEW_LISTENER: c_uint = 1 << 3;
+
+/// Syscalls that are always allowed.
+///
+/// This is the baseline that programs built on the Rust standard library need once they are
+/// running, with either glibc or musl: memory management, threads and their synchronization,
+/// signal handling, clocks and sleeping, and I/O on descriptors that are already open. Calls that
+/// also need their arguments checked, such as `clone`, `madvise`, and `fcntl`, are handled in
+/// `Filter::new`. On 32-bit architectures, the C library uses the versions of the time calls
+/// that take 64-bit times where the kernel has them.
+pub static ALLOWED_SYSCALLS: [Syscall; 41] = [
+    Syscall::Brk,
+    Syscall::ClockGetres,
+    Syscall::ClockGetresTime64,
+    Syscall::ClockGettime,
+    Syscall::ClockGettime64,
+    Syscall::ClockNanosleep,
+    Syscall::ClockNanosleepTime64,
+    Syscall::Close,
+    Syscall::Exit,
+    Syscall::ExitGroup,
+    Syscall::Futex,
+    Syscall::FutexTime64,
+    Syscall::Getpid,
+    Syscall::Getrandom,
+    Syscall::Gettid,
+    Syscall::Getuid,
+    Syscall::Mmap,
+    Syscall::Mprotect,
+    Syscall::Mremap,
+    Syscall::Munmap,
+    Syscall::Nanosleep,
+    Syscall::Poll,
+    Syscall::Ppoll,
+    Syscall::PpollTime64,
+    Syscall::Read,
+    Syscall::Readv,
+    Syscall::Recvfrom,
+    Syscall::Recvmsg,
+    Syscall::Rseq,
+    Syscall::RtSigaction,
+    Syscall::RtSigprocmask,
+    Syscall::RtSigreturn,
+    Syscall::SchedGetaffinity,
+    Syscall::SchedYield,
+    Syscall::Sendmmsg,
+    Syscall::Sendto,
+    Syscall::SetRobustList,
+    Syscall::SetTidAddress,
+    Syscall::Sigaltstack,
+    Syscall::Write,
+    Syscall::Writev,
 ];
 
-// A most untimely end...
-static FILTER_EPILOGUE: [sock_filter; 1] = [
-    KILL_PROCESS,
+static ALLOWED_SYSCALLS_FOR_FILE_READ: [Syscall; 15] = [
+    Syscall::Access,
+    Syscall::Faccessat,
+    Syscall::Faccessat2,
+    Syscall::Fstat,
+    Syscall::Fstatfs,
+    Syscall::Getdents64,
+    Syscall::Lseek,
+    Syscall::Lstat,
+    Syscall::Newfstatat,
+    Syscall::Pread64,
+    Syscall::Readlink,
+    Syscall::Readlinkat,
+    Syscall::Stat,
+    Syscall::Statfs,
+    Syscall::Statx,
 ];
 
-/// Syscalls that are always allowed.
-pub static ALLOWED_SYSCALLS: [u32; 21] = [
-    libc::SYS_brk as u32,
-    libc::SYS_close as u32,
-    libc::SYS_exit as u32,
-    libc::SYS_exit_group as u32,
-    libc::SYS_futex as u32,
-    libc::SYS_getrandom as u32,
-    libc::SYS_getuid as u32,
-    libc::SYS_mmap as u32,
-    libc::SYS_mprotect as u32,
-    libc::SYS_munmap as u32,
-    libc::SYS_poll as u32,
-    libc::SYS_read as u32,
-    libc::SYS_recvfrom as u32,
-    libc::SYS_recvmsg as u32,
-    libc::SYS_rt_sigreturn as u32,
-    libc::SYS_sched_getaffinity as u32,
-    libc::SYS_sendmmsg as u32,
-    libc::SYS_sendto as u32,
-    libc::SYS_set_robust_list as u32,
-    libc::SYS_sigaltstack as u32,
-    libc::SYS_write as u32,
+static ALLOWED_SYSCALLS_FOR_FILE_WRITE: [Syscall; 5] = [
+    Syscall::Fdatasync,
+    Syscall::Fsync,
+    Syscall::Ftruncate,
+    Syscall::Pwrite64,
+    Syscall::Truncate,
 ];
 
-static ALLOWED_SYSCALLS_FOR_FILE_READ: [u32; 5] = [
-    libc::SYS_access as u32,
-    libc::SYS_fstat as u32,
-    libc::SYS_lseek as u32,
-    libc::SYS_readlink as u32,
-    libc::SYS_stat as u32,
+static ALLOWED_SYSCALLS_FOR_FILE_CREATE_AND_UNLINK: [Syscall; 8] = [
+    Syscall::Mkdir,
+    Syscall::Mkdirat,
+    Syscall::Rename,
+    Syscall::Renameat,
+    Syscall::Renameat2,
+    Syscall::Rmdir,
+    Syscall::Unlink,
+    Syscall::Unlinkat,
 ];
 
-static ALLOWED_SYSCALLS_FOR_NETWORK_OUTBOUND: [u32; 3] = [
-    libc::SYS_bind as u32,
-    libc::SYS_connect as u32,
-    libc::SYS_getsockname as u32,
+/// Syscalls needed to create processes and to start the programs they execute. The dynamic
+/// linker and the C library set up each new program with these.
+static ALLOWED_SYSCALLS_FOR_EXEC: [Syscall; 10] = [
+    Syscall::ArchPrctl,
+    Syscall::Dup2,
+    Syscall::Dup3,
+    Syscall::Execve,
+    Syscall::Fork,
+    Syscall::Pipe2,
+    Syscall::SetThreadArea,
+    Syscall::Vfork,
+    Syscall::Wait4,
+    Syscall::Waitid,
+];
+
+static ALLOWED_SYSCALLS_FOR_SIGNAL_CHILDREN: [Syscall; 3] = [
+    Syscall::Kill,
+    Syscall::Tgkill,
+    Syscall::Tkill,
+];
+
+static ALLOWED_SYSCALLS_FOR_NETWORK_OUTBOUND: [Syscall; 3] = [
+    Syscall::Bind,
+    Syscall::Connect,
+    Syscall::Getsockname,
+];
+
+/// Syscalls needed to hand the notification descriptor to the supervisor, once the filter is in
+/// place.
+static ALLOWED_SYSCALLS_FOR_SUPERVISION: [Syscall; 1] = [
+    Syscall::Sendmsg,
+];
+
+/// Syscalls whose arguments the supervisor checks against the profile in audit mode.
+static AUDITED_SYSCALLS: [Syscall; 4] = [
+    Syscall::Connect,
+    Syscall::Execve,
+    Syscall::Open,
+    Syscall::Openat,
+];
+
+static ALLOWED_SYSCALLS_FOR_NETWORK_INBOUND: [Syscall; 6] = [
+    Syscall::Accept,
+    Syscall::Accept4,
+    Syscall::Bind,
+    Syscall::Getsockname,
+    Syscall::Getsockopt,
+    Syscall::Listen,
 ];
 
 const ALLOW_SYSCALL: sock_filter = sock_filter {
@@ -158,99 +219,196 @@ const ALLOW_SYSCALL: sock_filter = sock_filter {
     jf: 0,
 };
 
-const KILL_PROCESS: sock_filter = sock_filter {
+const NOTIFY_SUPERVISOR: sock_filter = sock_filter {
     code: RET + K,
-    k: SECCOMP_RET_KILL,
-    jt: 0,
-    jf: 0,
-};
-
-const EXAMINE_SYSCALL: sock_filter = sock_filter {
-    code: LD + W + ABS,
-    k: SYSCALL_NR_OFFSET,
-    jt: 0,
-    jf: 0,
-};
-
-const EXAMINE_ARG_0: sock_filter = sock_filter {
-    code: LD + W + ABS,
-    k: ARG_0_OFFSET,
+    k: SECCOMP_RET_USER_NOTIF,
     jt: 0,
     jf: 0,
 };
 
-const EXAMINE_ARG_1: sock_filter = sock_filter {
-    code: LD + W + ABS,
-    k: ARG_1_OFFSET,
+const KILL_PROCESS: sock_filter = sock_filter {
+    code: RET + K,
+    k: SECCOMP_RET_KILL_PROCESS,
     jt: 0,
     jf: 0,
 };
 
-const EXAMINE_ARG_2: sock_filter = sock_filter {
+const EXAMINE_SYSCALL: sock_filter = sock_filter {
     code: LD + W + ABS,
-    k: ARG_2_OFFSET,
+    k: SYSCALL_NR_OFFSET,
     jt: 0,
     jf: 0,
 };
 
-const VALIDATE_ARCHITECTURE_0: sock_filter = sock_filter {
+const EXAMINE_ARCHITECTURE: sock_filter = sock_filter {
     code: LD + W + ABS,
     k: ARCH_NR_OFFSET,
     jt: 0,
     jf: 0,
 };
 
-const VALIDATE_ARCHITECTURE_1: sock_filter = sock_filter {
-    code: JMP + JEQ + K,
-    k: ARCH_NR,
-    jt: 1,
-    jf: 0,
-};
-
-const VALIDATE_ARCHITECTURE_2: sock_filter = KILL_PROCESS;
-
 pub struct Filter {
     program: Vec<sock_filter>,
+    /// The architecture whose system call numbers the filter uses.
+    architecture: &'static Architecture,
+    /// Whether any violation raises `SIGSYS`, so that a handler must be installed first.
+    traps: bool,
 }
 
 impl Filter {
     pub fn new(profile: &Profile) -> Filter {
+        Filter::build(profile, false, syscalls::HOST)
+    }
+
+    /// Creates a filter for audit mode, which allows the same system calls as `new` and hands
+    /// the rest to the supervisor instead of acting on the profile's violation policy. Calls that
+    /// open files, execute programs, or connect sockets go to the supervisor too, so that it can
+    /// check their arguments.
+    pub fn audit(profile: &Profile) -> Filter {
+        Filter::build(profile, true, syscalls::HOST)
+    }
+
+    /// Builds the filter for `architecture`, which need not be the one it will run on.
+    fn build(profile: &Profile, audit: bool, architecture: &'static Architecture) -> Filter {
         let mut filter = Filter {
-            program: FILTER_PROLOGUE.iter().map(|x| *x).collect(),
+            program: vec![],
+            architecture,
+            traps: !audit && profile.violation_policy().traps(),
         };
-        filter.allow_syscalls(&ALLOWED_SYSCALLS);
 
-        if profile.allowed_operations().iter().any(|operation| {
-            match *operation {
-                Operation::FileReadAll(_) | Operation::FileReadMetadata(_) => true,
-                _ => false,
+        // System call numbers differ between architectures, so kill anything that uses another
+        // architecture's.
+        filter.program.push(EXAMINE_ARCHITECTURE);
+        filter.if_k_isnt(architecture.audit_arch, |filter| filter.program.push(KILL_PROCESS));
+
+        // x32 programs share the x86-64 architecture number and set a bit in the system call
+        // number instead. The rules below are written for the native ABI only, so kill those
+        // too, whatever the profile's violation policy says.
+        if let Some(abi_syscall_bit) = architecture.abi_syscall_bit {
+            filter.program.push(EXAMINE_SYSCALL);
+            filter.if_k_has_set(abi_syscall_bit, |filter| filter.program.push(KILL_PROCESS));
+        }
+
+        filter.allow_syscalls(&ALLOWED_SYSCALLS);
+        if audit {
+            filter.allow_syscalls(&ALLOWED_SYSCALLS_FOR_SUPERVISION);
+            for syscall in AUDITED_SYSCALLS.iter() {
+                filter.if_syscall_is(*syscall, |filter| filter.notify_supervisor())
             }
-        }) {
+        }
+
+        // Work out which flags `open` may be called with. The namespace jail takes care of
+        // restricting which paths these apply to.
+        let mut allowed_open_flags = None;
+        for operation in profile.allowed_operations().iter() {
+            let flags = match *operation {
+                // The dynamic linker has to read the libraries of the programs that it starts.
+                Operation::FileReadAll(_) |
+                Operation::FileReadMetadata(_) |
+                Operation::Exec(_) => {
+                    O_RDONLY | O_CLOEXEC | O_DIRECTORY | O_NOCTTY | O_NONBLOCK
+                }
+                Operation::FileWrite(_) => {
+                    O_WRONLY | O_RDWR | O_APPEND | O_TRUNC | O_CLOEXEC | O_NOCTTY | O_NONBLOCK
+                }
+                Operation::FileCreateAndUnlink(_) => O_CREAT | O_EXCL,
+                Operation::DeviceAccess(_) => {
+                    O_RDONLY | O_WRONLY | O_RDWR | O_CLOEXEC | O_NOCTTY | O_NONBLOCK
+                }
+                Operation::ScratchDirectory { .. } => {
+                    O_RDONLY | O_WRONLY | O_RDWR | O_APPEND | O_TRUNC | O_CREAT | O_EXCL |
+                        O_CLOEXEC | O_DIRECTORY | O_NOCTTY | O_NONBLOCK
+                }
+                _ => continue,
+            };
+            allowed_open_flags = Some(allowed_open_flags.unwrap_or(0) | flags);
+        }
+
+        if let Some(allowed_open_flags) = allowed_open_flags {
+            // 32-bit C libraries open everything with `O_LARGEFILE`, which is 0 on 64-bit ones.
+            let allowed_open_flags = allowed_open_flags | O_LARGEFILE;
             filter.allow_syscalls(&ALLOWED_SYSCALLS_FOR_FILE_READ);
 
-            // Only allow file reading.
-            filter.if_syscall_is(libc::SYS_open as u32, |filter| {
-                filter.if_arg1_hasnt_set(!(O_RDONLY | O_CLOEXEC | O_NOCTTY | O_NONBLOCK) as u32,
+            filter.if_syscall_is(Syscall::Open, |filter| {
+                filter.if_arg1_hasnt_set(!allowed_open_flags as u32,
+                                         |filter| filter.allow_this_syscall())
+            });
+            filter.if_syscall_is(Syscall::Openat, |filter| {
+                filter.if_arg2_hasnt_set(!allowed_open_flags as u32,
                                          |filter| filter.allow_this_syscall())
             });
 
             // Only allow the `FIONREAD` or `FIOCLEX` `ioctl`s to be performed.
-            filter.if_syscall_is(libc::SYS_ioctl as u32, |filter| {
+            filter.if_syscall_is(Syscall::Ioctl, |filter| {
                 filter.if_arg1_is(FIONREAD as u32, |filter| filter.allow_this_syscall());
                 filter.if_arg1_is(FIOCLEX as u32, |filter| filter.allow_this_syscall())
             })
         }
 
         if profile.allowed_operations().iter().any(|operation| {
-            match *operation {
-                Operation::NetworkOutbound(_) => true,
-                _ => false,
-            }
+            matches!(*operation, Operation::FileWrite(_) | Operation::ScratchDirectory { .. })
+        }) {
+            filter.allow_syscalls(&ALLOWED_SYSCALLS_FOR_FILE_WRITE);
+        }
+
+        if profile.allowed_operations().iter().any(|operation| {
+            matches!(*operation,
+                     Operation::FileCreateAndUnlink(_) | Operation::ScratchDirectory { .. })
         }) {
+            filter.allow_syscalls(&ALLOWED_SYSCALLS_FOR_FILE_CREATE_AND_UNLINK);
+        }
+
+        let allows_network_outbound = profile.allowed_operations().iter().any(|operation| {
+            matches!(*operation, Operation::NetworkOutbound(_))
+        });
+        let allows_network_inbound = profile.allowed_operations().iter().any(|operation| {
+            matches!(*operation, Operation::NetworkInbound(_))
+        });
+        if supervisor::requires_supervisor(profile) {
+            // Only the supervisor can tell which addresses `connect` may reach.
+            filter.allow_syscalls(&ALLOWED_SYSCALLS_FOR_SUPERVISION);
+            filter.if_syscall_is(Syscall::Connect, |filter| filter.notify_supervisor());
+        }
+        if allows_network_outbound {
             filter.allow_syscalls(&ALLOWED_SYSCALLS_FOR_NETWORK_OUTBOUND);
+        }
+        if allows_network_inbound {
+            filter.allow_syscalls(&ALLOWED_SYSCALLS_FOR_NETWORK_INBOUND);
 
+            // Servers commonly need to set options like `SO_REUSEADDR` and `IPV6_V6ONLY`; allow
+            // socket-, TCP-, and IPv6-level options only.
+            filter.if_syscall_is(Syscall::Setsockopt, |filter| {
+                filter.if_arg1_is(SOL_SOCKET as u32, |filter| filter.allow_this_syscall());
+                filter.if_arg1_is(IPPROTO_TCP as u32, |filter| filter.allow_this_syscall());
+                filter.if_arg1_is(IPPROTO_IPV6 as u32, |filter| filter.allow_this_syscall())
+            })
+        }
+        let allows_only_tcp_ports = profile.allowed_operations().iter().all(|operation| {
+            match *operation {
+                Operation::NetworkOutbound(AddressPattern::Tcp(_)) |
+                Operation::NetworkInbound(AddressPattern::Tcp(_)) => true,
+                Operation::NetworkOutbound(_) | Operation::NetworkInbound(_) => false,
+                _ => true,
+            }
+        });
+        if (allows_network_outbound || allows_network_inbound) && allows_only_tcp_ports {
+            // Landlock restricts the ports that TCP sockets may use, but nothing else, so only
+            // allow IPv4 and IPv6 TCP sockets to be created.
+            let other_types = !(SOCK_STREAM | SOCK_NONBLOCK | SOCK_CLOEXEC) as u32;
+            filter.if_syscall_is(Syscall::Socket, |filter| {
+                for family in [AF_INET, AF_INET6].iter() {
+                    filter.if_arg0_is(*family as u32, |filter| {
+                        filter.if_arg1_hasnt_set(other_types, |filter| {
+                            filter.if_arg2_is(0, |filter| filter.allow_this_syscall());
+                            filter.if_arg2_is(IPPROTO_TCP as u32,
+                                              |filter| filter.allow_this_syscall())
+                        })
+                    })
+                }
+            })
+        } else if allows_network_outbound || allows_network_inbound {
             // Only allow Unix, IPv4, IPv6, and netlink route sockets to be created.
-            filter.if_syscall_is(libc::SYS_socket as u32, |filter| {
+            filter.if_syscall_is(Syscall::Socket, |filter| {
                 filter.if_arg0_is(AF_UNIX as u32, |filter| filter.allow_this_syscall());
                 filter.if_arg0_is(AF_INET as u32, |filter| filter.allow_this_syscall());
                 filter.if_arg0_is(AF_INET6 as u32, |filter| filter.allow_this_syscall());
@@ -260,22 +418,101 @@ impl Filter {
             })
         }
 
-        // Only allow normal threads to be created.
-        filter.if_syscall_is(libc::SYS_clone as u32, |filter| {
-            filter.if_arg0_is((CLONE_VM |
-                               CLONE_FS |
-                               CLONE_FILES |
-                               CLONE_SIGHAND |
-                               CLONE_THREAD |
-                               CLONE_SYSVSEM |
-                               CLONE_SETTLS |
-                               CLONE_PARENT_SETTID |
-                               CLONE_CHILD_CLEARTID) as u32,
-                              |filter| filter.allow_this_syscall())
+        let allows_exec = profile.allowed_operations().iter().any(|operation| {
+            matches!(*operation, Operation::Exec(_))
+        });
+        if allows_exec {
+            filter.allow_syscalls(&ALLOWED_SYSCALLS_FOR_EXEC);
+        }
+
+        // `clone3` takes its flags in a structure that BPF can't look into. Pretend the kernel
+        // doesn't have it, so that the C library falls back to `clone`.
+        filter.if_syscall_is(Syscall::Clone3,
+                             |filter| filter.fail_this_syscall(libc::ENOSYS));
+
+        // On 32-bit x86 and on PowerPC, `socketcall` and `ipc` multiplex the socket and System V
+        // IPC calls, and their arguments are in memory too, so the rules for `socket` and the rest
+        // can't be applied to them. Every kernel that supports this filter has the separate calls
+        // too, and C libraries built for those kernels use them, so refuse the multiplexers.
+        filter.if_syscall_is(Syscall::Socketcall,
+                             |filter| filter.fail_this_syscall(libc::ENOSYS));
+        filter.if_syscall_is(Syscall::Ipc, |filter| filter.fail_this_syscall(libc::ENOSYS));
+
+        if allows_exec {
+            // The C library looks up the stack size limit as each program starts, but it may
+            // not change any limits.
+            filter.if_syscall_is(Syscall::Prlimit64, |filter| {
+                filter.if_arg2_is(0, |filter| {
+                    filter.if_arg2_high_is(0, |filter| filter.allow_this_syscall())
+                })
+            })
+        }
+
+        filter.if_syscall_is(Syscall::Clone, |filter| {
+            if allows_exec {
+                // Allow new processes to be created, as long as they don't escape into new
+                // namespaces.
+                filter.if_arg0_hasnt_set((CLONE_NEWCGROUP |
+                                          CLONE_NEWIPC |
+                                          CLONE_NEWNET |
+                                          CLONE_NEWNS |
+                                          CLONE_NEWPID |
+                                          CLONE_NEWUSER |
+                                          CLONE_NEWUTS) as u32,
+                                         |filter| filter.allow_this_syscall())
+            } else {
+                // Only allow normal threads to be created.
+                filter.if_arg0_is((CLONE_VM |
+                                   CLONE_FS |
+                                   CLONE_FILES |
+                                   CLONE_SIGHAND |
+                                   CLONE_THREAD |
+                                   CLONE_SYSVSEM |
+                                   CLONE_SETTLS |
+                                   CLONE_PARENT_SETTID |
+                                   CLONE_CHILD_CLEARTID) as u32,
+                                  |filter| filter.allow_this_syscall())
+            }
+        });
+
+        if profile.allowed_operations().iter().any(|operation| {
+            match *operation {
+                Operation::SignalChildren => true,
+                _ => false,
+            }
+        }) {
+            // The PID namespace ensures that every process we can name was created inside the
+            // sandbox.
+            filter.allow_syscalls(&ALLOWED_SYSCALLS_FOR_SIGNAL_CHILDREN);
+        } else if profile.allowed_operations().iter().any(|operation| {
+            match *operation {
+                Operation::SignalSelf => true,
+                _ => false,
+            }
+        }) {
+            // Only allow signals to be sent to our own process and threads. The filter is built
+            // in the process being sandboxed, so `getpid()` is the right process.
+            let pid = unsafe {
+                libc::getpid()
+            } as u32;
+            filter.if_syscall_is(Syscall::Kill, |filter| {
+                filter.if_arg0_is(pid, |filter| filter.allow_this_syscall())
+            });
+            filter.if_syscall_is(Syscall::Tgkill, |filter| {
+                filter.if_arg0_is(pid, |filter| filter.allow_this_syscall())
+            });
+        }
+
+        // Only allow descriptor flags to be read and set, and descriptors to be duplicated, with
+        // `fcntl`.
+        filter.if_syscall_is(Syscall::Fcntl, |filter| {
+            for command in [F_DUPFD_CLOEXEC, F_GETFD, F_GETFL, F_SETFD].iter() {
+                filter.if_arg1_is(*command as u32, |filter| filter.allow_this_syscall())
+            }
         });
 
         // Only allow the POSIX values for `madvise`.
-        filter.if_syscall_is(libc::SYS_madvise as u32, |filter| {
+        filter.if_syscall_is(Syscall::Madvise, |filter| {
             for mode in [
                 MADV_NORMAL,
                 MADV_RANDOM,
@@ -287,7 +524,17 @@ impl Filter {
             }
         });
 
-        filter.program.extend_from_slice(&FILTER_EPILOGUE);
+        // Everything else is a violation, which meets a most untimely end unless the profile
+        // asks for something gentler. In audit mode, the supervisor just writes it down.
+        if audit {
+            filter.notify_supervisor();
+            return filter
+        }
+        let policy = profile.violation_policy();
+        for entry in policy.overrides.iter() {
+            filter.if_syscall_number_is(entry.syscall, |filter| filter.take_action(entry.action))
+        }
+        filter.take_action(policy.default_action);
         filter
     }
 
@@ -312,11 +559,12 @@ impl Filter {
 
     /// Activates this filter, applying all of its restrictions forevermore. This can only be done
     /// once.
-    pub fn activate(&self) -> Result<(),c_int> {
+    pub fn activate(&self) -> Result<(),Error> {
+        try!(self.install_trap_handler());
         unsafe {
             let result = libc::prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0);
             if result != 0 {
-                return Err(result)
+                return Err(error::last_os_error(Stage::SetNoNewPrivileges, None))
             }
 
             let program = sock_fprog {
@@ -331,46 +579,152 @@ impl Filter {
             if result == 0 {
                 Ok(())
             } else {
-                Err(result)
+                Err(error::last_os_error(Stage::InstallSeccompFilter, None))
+            }
+        }
+    }
+
+    /// Activates this filter like `activate`, and returns a descriptor through which the
+    /// supervisor is notified of the system calls that the filter hands to it.
+    pub fn activate_with_listener(&self) -> Result<c_int,Error> {
+        try!(self.install_trap_handler());
+        unsafe {
+            let result = libc::prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0);
+            if result != 0 {
+                return Err(error::last_os_error(Stage::SetNoNewPrivileges, None))
+            }
+
+            let program = sock_fprog {
+                len: self.program.len() as c_ushort,
+                filter: self.program.as_ptr(),
+            };
+            let listener = libc::syscall(libc::SYS_seccomp,
+                                         SECCOMP_SET_MODE_FILTER,
+                                         SECCOMP_FILTER_FLAG_NEW_LISTENER,
+                                         &program as *const sock_fprog);
+            if listener >= 0 {
+                Ok(listener as c_int)
+            } else {
+                Err(error::last_os_error(Stage::InstallSeccompFilter, None))
+            }
+        }
+    }
+
+    /// Installs the `SIGSYS` handler that records trapped system calls, if the filter traps any.
+    fn install_trap_handler(&self) -> Result<(),Error> {
+        if !self.traps {
+            return Ok(())
+        }
+        unsafe {
+            let mut action: libc::sigaction = mem::zeroed();
+            let handler: extern "C" fn(c_int, *mut libc::siginfo_t, *mut c_void) = handle_sigsys;
+            action.sa_sigaction = handler as usize;
+            action.sa_flags = libc::SA_SIGINFO;
+            libc::sigemptyset(&mut action.sa_mask);
+            if libc::sigaction(libc::SIGSYS, &action, ptr::null_mut()) != 0 {
+                return Err(error::last_os_error(Stage::InstallTrapHandler, None))
             }
         }
+        Ok(())
+    }
+
+    fn take_action(&mut self, action: ViolationAction) {
+        let value = match action {
+            ViolationAction::KillProcess => SECCOMP_RET_KILL_PROCESS,
+            ViolationAction::KillThread => SECCOMP_RET_KILL_THREAD,
+            ViolationAction::Errno(errno) => SECCOMP_RET_ERRNO | (errno as u32 & SECCOMP_RET_DATA),
+            ViolationAction::Trap => SECCOMP_RET_TRAP,
+        };
+        self.program.push(sock_filter {
+            code: RET + K,
+            k: value,
+            jt: 0,
+            jf: 0,
+        })
     }
 
     fn allow_this_syscall(&mut self) {
         self.program.push(ALLOW_SYSCALL)
     }
 
-    fn allow_syscalls(&mut self, syscalls: &[u32]) {
-        for &syscall in syscalls.iter() {
-            self.if_syscall_is(syscall, |filter| filter.allow_this_syscall())
+    fn fail_this_syscall(&mut self, errno: c_int) {
+        self.take_action(ViolationAction::Errno(errno as u16))
+    }
+
+    fn notify_supervisor(&mut self) {
+        self.program.push(NOTIFY_SUPERVISOR)
+    }
+
+    fn allow_syscalls(&mut self, syscalls: &[Syscall]) {
+        for syscall in syscalls.iter() {
+            self.if_syscall_is(*syscall, |filter| filter.allow_this_syscall())
+        }
+    }
+
+    /// Adds the instructions that `then` adds, to run when the system call is `syscall`. Nothing
+    /// is added if the architecture doesn't have that system call.
+    fn if_syscall_is<F>(&mut self, syscall: Syscall, then: F) where F: FnMut(&mut Filter) {
+        if let Some(number) = self.architecture.number(syscall) {
+            self.if_syscall_number_is(number, then)
         }
     }
 
-    fn if_syscall_is<F>(&mut self, number: u32, then: F) where F: FnMut(&mut Filter) {
+    fn if_syscall_number_is<F>(&mut self, number: u32, then: F) where F: FnMut(&mut Filter) {
         self.program.push(EXAMINE_SYSCALL);
         self.if_k_is(number, then)
     }
 
+    /// Loads the low or high half of argument `index`. Big-endian architectures store the high
+    /// half first.
+    fn examine_arg(&mut self, index: u32, high_half: bool) {
+        let mut offset = ARG_0_OFFSET + index * ARG_SIZE;
+        if high_half == self.architecture.is_little_endian() {
+            offset += 4
+        }
+        self.program.push(sock_filter {
+            code: LD + W + ABS,
+            k: offset,
+            jt: 0,
+            jf: 0,
+        })
+    }
+
     fn if_arg0_is<F>(&mut self, value: u32, then: F) where F: FnMut(&mut Filter) {
-        self.program.push(EXAMINE_ARG_0);
+        self.examine_arg(0, false);
         self.if_k_is(value, then)
     }
 
+    fn if_arg0_hasnt_set<F>(&mut self, value: u32, then: F) where F: FnMut(&mut Filter) {
+        self.examine_arg(0, false);
+        self.if_k_hasnt_set(value, then)
+    }
+
     fn if_arg1_is<F>(&mut self, value: u32, then: F) where F: FnMut(&mut Filter) {
-        self.program.push(EXAMINE_ARG_1);
+        self.examine_arg(1, false);
         self.if_k_is(value, then)
     }
 
     fn if_arg1_hasnt_set<F>(&mut self, value: u32, then: F) where F: FnMut(&mut Filter) {
-        self.program.push(EXAMINE_ARG_1);
+        self.examine_arg(1, false);
         self.if_k_hasnt_set(value, then)
     }
 
     fn if_arg2_is<F>(&mut self, value: u32, then: F) where F: FnMut(&mut Filter) {
-        self.program.push(EXAMINE_ARG_2);
+        self.examine_arg(2, false);
+        self.if_k_is(value, then)
+    }
+
+    /// Checks the high half of the third argument, which `if_arg2_is` doesn't look at.
+    fn if_arg2_high_is<F>(&mut self, value: u32, then: F) where F: FnMut(&mut Filter) {
+        self.examine_arg(2, true);
         self.if_k_is(value, then)
     }
 
+    fn if_arg2_hasnt_set<F>(&mut self, value: u32, then: F) where F: FnMut(&mut Filter) {
+        self.examine_arg(2, false);
+        self.if_k_hasnt_set(value, then)
+    }
+
     fn if_k_is<F>(&mut self, value: u32, mut then: F) where F: FnMut(&mut Filter) {
         let index = self.program.len();
         self.program.push(sock_filter {
@@ -383,6 +737,30 @@ impl Filter {
         self.program[index].jf = (self.program.len() - index - 1) as u8;
     }
 
+    fn if_k_isnt<F>(&mut self, value: u32, mut then: F) where F: FnMut(&mut Filter) {
+        let index = self.program.len();
+        self.program.push(sock_filter {
+            code: JMP + JEQ + K,
+            k: value,
+            jt: 0,
+            jf: 0,
+        });
+        then(self);
+        self.program[index].jt = (self.program.len() - index - 1) as u8;
+    }
+
+    fn if_k_has_set<F>(&mut self, value: u32, mut then: F) where F: FnMut(&mut Filter) {
+        let index = self.program.len();
+        self.program.push(sock_filter {
+            code: JMP + JSET + K,
+            k: value,
+            jt: 0,
+            jf: 0,
+        });
+        then(self);
+        self.program[index].jf = (self.program.len() - index - 1) as u8;
+    }
+
     fn if_k_hasnt_set<F>(&mut self, value: u32, mut then: F) where F: FnMut(&mut Filter) {
         let index = self.program.len();
         self.program.push(sock_filter {
@@ -396,6 +774,82 @@ impl Filter {
     }
 }
 
+/// One more than the number of the last system call trapped in this process, or 0 if none has
+/// been.
+static LAST_TRAPPED_SYSCALL: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the number of the system call that most recently raised `SIGSYS` because the profile
+/// didn't allow it and its violation action was `ViolationAction::Trap`.
+///
+/// The handler that records this is installed when the sandbox is activated. A program that
+/// installs its own `SIGSYS` handler afterwards replaces it.
+pub fn last_trapped_syscall() -> Option<u32> {
+    match LAST_TRAPPED_SYSCALL.load(Ordering::SeqCst) {
+        0 => None,
+        syscall => Some((syscall - 1) as u32),
+    }
+}
+
+/// The layout of `siginfo_t` for `SIGSYS`, which the `libc` crate doesn't describe.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct siginfo_sigsys {
+    si_signo: c_int,
+    si_errno: c_int,
+    si_code: c_int,
+    call_addr: *mut c_void,
+    syscall: c_int,
+    arch: c_uint,
+}
+
+extern "C" fn handle_sigsys(_: c_int, info: *mut libc::siginfo_t, context: *mut c_void) {
+    unsafe {
+        let info = &*(info as *const siginfo_sigsys);
+        LAST_TRAPPED_SYSCALL.store(info.syscall as usize + 1, Ordering::SeqCst);
+        set_syscall_result(context, -libc::ENOSYS as i64);
+    }
+}
+
+/// Sets the value that the trapped system call appears to return, which would otherwise be
+/// whatever was left in the register.
+#[cfg(target_arch="x86")]
+unsafe fn set_syscall_result(context: *mut c_void, result: i64) {
+    let context = &mut *(context as *mut libc::ucontext_t);
+    context.uc_mcontext.gregs[libc::REG_EAX as usize] = result as i32;
+}
+
+#[cfg(target_arch="x86_64")]
+unsafe fn set_syscall_result(context: *mut c_void, result: i64) {
+    let context = &mut *(context as *mut libc::ucontext_t);
+    context.uc_mcontext.gregs[libc::REG_RAX as usize] = result;
+}
+
+#[cfg(target_arch="arm")]
+unsafe fn set_syscall_result(context: *mut c_void, result: i64) {
+    let context = &mut *(context as *mut libc::ucontext_t);
+    context.uc_mcontext.arm_r0 = result as libc::c_ulong;
+}
+
+#[cfg(target_arch="aarch64")]
+unsafe fn set_syscall_result(context: *mut c_void, result: i64) {
+    let context = &mut *(context as *mut libc::ucontext_t);
+    context.uc_mcontext.regs[0] = result as u64;
+}
+
+/// The result goes in `a0`, which is `x10`.
+#[cfg(target_arch="riscv64")]
+unsafe fn set_syscall_result(context: *mut c_void, result: i64) {
+    let context = &mut *(context as *mut libc::ucontext_t);
+    context.uc_mcontext.__gregs[10] = result as libc::c_ulong;
+}
+
+#[cfg(not(any(target_arch="x86",
+              target_arch="x86_64",
+              target_arch="arm",
+              target_arch="aarch64",
+              target_arch="riscv64")))]
+unsafe fn set_syscall_result(_: *mut c_void, _: i64) {}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 struct sock_filter {
@@ -411,3 +865,258 @@ struct sock_fprog {
     len: c_ushort,
     filter: *const sock_filter,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ABS, ARCH_NR_OFFSET, ARG_0_OFFSET, ARG_SIZE, JEQ, JMP, JSET, K, LD, RET, W};
+    use super::SYSCALL_NR_OFFSET;
+    use super::{ALLOWED_SYSCALLS, Filter, SECCOMP_RET_ALLOW, SECCOMP_RET_ERRNO};
+    use super::{SECCOMP_RET_KILL_PROCESS, SECCOMP_RET_USER_NOTIF};
+    use error::{Error, Stage};
+    use platform::linux::syscalls::{Architecture, Syscall, AARCH64, ARM, RISCV64, X86, X86_64};
+    use platform::linux::syscalls::{POWERPC, POWERPC64, POWERPC64LE};
+    use profile::{AddressPattern, Operation, PathPattern, Profile, ViolationAction};
+    use profile::ViolationOverride;
+    use profile::ViolationPolicy;
+
+    use libc::{self, O_CLOEXEC, O_RDONLY, O_WRONLY};
+    use std::path::PathBuf;
+
+    static ARCHITECTURES: [&Architecture; 8] = [
+        &X86, &X86_64, &ARM, &AARCH64, &POWERPC, &POWERPC64, &POWERPC64LE, &RISCV64,
+    ];
+
+    /// A system call number that no architecture uses.
+    const UNKNOWN_SYSCALL: u32 = 1000;
+
+    /// Runs `filter` over a system call the way the kernel would, returning its verdict.
+    fn run(filter: &Filter, audit_arch: u32, number: u32, args: [u64; 6]) -> u32 {
+        let mut accumulator = 0;
+        let mut pc = 0;
+        loop {
+            let instruction = filter.program[pc];
+            pc += 1;
+            let (code, k) = (instruction.code, instruction.k);
+            if code == LD + W + ABS {
+                accumulator = match k {
+                    SYSCALL_NR_OFFSET => number,
+                    ARCH_NR_OFFSET => audit_arch,
+                    _ => {
+                        let arg = args[((k - ARG_0_OFFSET) / ARG_SIZE) as usize];
+                        let second = (k - ARG_0_OFFSET) % ARG_SIZE == 4;
+                        if second == filter.architecture.is_little_endian() {
+                            (arg >> 32) as u32
+                        } else {
+                            arg as u32
+                        }
+                    }
+                }
+            } else if code == JMP + JEQ + K {
+                pc += if accumulator == k { instruction.jt } else { instruction.jf } as usize
+            } else if code == JMP + JSET + K {
+                pc += if accumulator & k != 0 { instruction.jt } else { instruction.jf } as usize
+            } else if code == RET + K {
+                return k
+            } else {
+                panic!("unexpected instruction {:#x}", code)
+            }
+        }
+    }
+
+    /// Runs the filter built for `architecture` over `syscall`, which it must have.
+    fn verdict(filter: &Filter, architecture: &Architecture, syscall: Syscall, args: [u64; 6])
+               -> u32 {
+        run(filter, architecture.audit_arch, architecture.number(syscall).unwrap(), args)
+    }
+
+    fn profile(operations: Vec<Operation>) -> Profile {
+        Profile::new(operations).unwrap()
+    }
+
+    fn read_profile() -> Profile {
+        profile(vec![Operation::FileReadAll(PathPattern::Subpath(PathBuf::from("/usr")))])
+    }
+
+    #[test]
+    fn baseline_is_allowed() {
+        for architecture in ARCHITECTURES.iter() {
+            let filter = Filter::build(&profile(vec![]), false, architecture);
+            for syscall in ALLOWED_SYSCALLS.iter() {
+                if let Some(number) = architecture.number(*syscall) {
+                    assert_eq!(run(&filter, architecture.audit_arch, number, [0; 6]),
+                               SECCOMP_RET_ALLOW,
+                               "{:?}",
+                               syscall);
+                }
+            }
+        }
+    }
+
+    /// This includes 32-bit x86 programs on x86-64.
+    #[test]
+    fn other_architectures_are_killed() {
+        for architecture in ARCHITECTURES.iter() {
+            let filter = Filter::build(&profile(vec![]), false, architecture);
+            for other in ARCHITECTURES.iter() {
+                let read = other.number(Syscall::Read).unwrap();
+                let expected = if other.audit_arch == architecture.audit_arch {
+                    SECCOMP_RET_ALLOW
+                } else {
+                    SECCOMP_RET_KILL_PROCESS
+                };
+                assert_eq!(run(&filter, other.audit_arch, read, [0; 6]), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_syscalls_are_killed() {
+        for architecture in ARCHITECTURES.iter() {
+            let filter = Filter::build(&read_profile(), false, architecture);
+            assert_eq!(run(&filter, architecture.audit_arch, UNKNOWN_SYSCALL, [0; 6]),
+                       SECCOMP_RET_KILL_PROCESS);
+        }
+    }
+
+    #[test]
+    fn x32_syscalls_are_killed() {
+        let mut profile = read_profile();
+        profile.set_violation_policy(ViolationPolicy {
+            default_action: ViolationAction::Errno(libc::EPERM as u16),
+            overrides: vec![],
+        }).unwrap();
+        let filter = Filter::build(&profile, false, &X86_64);
+        let x32_bit = X86_64.abi_syscall_bit.unwrap();
+        for syscall in [Syscall::Read, Syscall::Openat, Syscall::Connect].iter() {
+            let number = X86_64.number(*syscall).unwrap();
+            assert_eq!(run(&filter, X86_64.audit_arch, number | x32_bit, [0; 6]),
+                       SECCOMP_RET_KILL_PROCESS,
+                       "{:?}",
+                       syscall);
+        }
+        assert_eq!(run(&filter, X86_64.audit_arch, UNKNOWN_SYSCALL, [0; 6]),
+                   SECCOMP_RET_ERRNO | libc::EPERM as u32);
+    }
+
+    #[test]
+    fn socketcall_and_ipc_fail_with_enosys() {
+        let network = Operation::NetworkOutbound(AddressPattern::All);
+        for profile in [profile(vec![]), profile(vec![network])].iter() {
+            for architecture in [&X86, &POWERPC, &POWERPC64, &POWERPC64LE].iter() {
+                let filter = Filter::build(profile, false, architecture);
+                for syscall in [Syscall::Socketcall, Syscall::Ipc].iter() {
+                    assert_eq!(verdict(&filter, architecture, *syscall, [1, 0, 0, 0, 0, 0]),
+                               SECCOMP_RET_ERRNO | libc::ENOSYS as u32,
+                               "{:?}",
+                               syscall);
+                }
+                let packet = libc::AF_PACKET as u64;
+                assert_eq!(verdict(&filter, architecture, Syscall::Socket,
+                                   [packet, 0, 0, 0, 0, 0]),
+                           SECCOMP_RET_KILL_PROCESS);
+            }
+        }
+    }
+
+    #[test]
+    fn openat_flags_are_checked() {
+        let read_only = (O_RDONLY | O_CLOEXEC) as u64;
+        let write_only = (O_WRONLY | O_CLOEXEC) as u64;
+        for architecture in ARCHITECTURES.iter() {
+            let filter = Filter::build(&read_profile(), false, architecture);
+            assert_eq!(verdict(&filter, architecture, Syscall::Openat, [0, 0, read_only, 0, 0, 0]),
+                       SECCOMP_RET_ALLOW);
+            assert_eq!(verdict(&filter, architecture, Syscall::Openat, [0, 0, write_only, 0, 0, 0]),
+                       SECCOMP_RET_KILL_PROCESS);
+            assert_eq!(verdict(&filter, architecture, Syscall::Newfstatat, [0; 6]),
+                       SECCOMP_RET_ALLOW);
+
+            // Without any file operations, nothing may be opened at all.
+            let filter = Filter::build(&profile(vec![]), false, architecture);
+            assert_eq!(verdict(&filter, architecture, Syscall::Openat, [0, 0, read_only, 0, 0, 0]),
+                       SECCOMP_RET_KILL_PROCESS);
+        }
+    }
+
+    #[test]
+    fn legacy_open_flags_are_checked_on_x86_64() {
+        let filter = Filter::build(&read_profile(), false, &X86_64);
+        assert_eq!(verdict(&filter, &X86_64, Syscall::Open, [0, O_RDONLY as u64, 0, 0, 0, 0]),
+                   SECCOMP_RET_ALLOW);
+        assert_eq!(verdict(&filter, &X86_64, Syscall::Open, [0, O_WRONLY as u64, 0, 0, 0, 0]),
+                   SECCOMP_RET_KILL_PROCESS);
+    }
+
+    #[test]
+    fn clone3_fails_with_enosys() {
+        for architecture in ARCHITECTURES.iter() {
+            let filter = Filter::build(&profile(vec![]), false, architecture);
+            assert_eq!(verdict(&filter, architecture, Syscall::Clone3, [0; 6]),
+                       SECCOMP_RET_ERRNO | libc::ENOSYS as u32);
+        }
+    }
+
+    #[test]
+    fn prlimit64_may_only_read_limits() {
+        let exec = Operation::Exec(PathPattern::Subpath(PathBuf::from("/usr/bin")));
+        for architecture in ARCHITECTURES.iter() {
+            let filter = Filter::build(&profile(vec![exec.clone()]), false, architecture);
+            assert_eq!(verdict(&filter, architecture, Syscall::Prlimit64, [0, 3, 0, 1, 0, 0]),
+                       SECCOMP_RET_ALLOW);
+            assert_eq!(verdict(&filter, architecture, Syscall::Prlimit64, [0, 3, 1, 1, 0, 0]),
+                       SECCOMP_RET_KILL_PROCESS);
+            // A pointer whose low half is zero still sets limits.
+            assert_eq!(verdict(&filter, architecture, Syscall::Prlimit64,
+                               [0, 3, 0x7f12_0000_0000, 1, 0, 0]),
+                       SECCOMP_RET_KILL_PROCESS);
+        }
+    }
+
+    #[test]
+    fn overrides_use_raw_numbers() {
+        let mut profile = profile(vec![]);
+        profile.set_violation_policy(ViolationPolicy {
+            default_action: ViolationAction::KillProcess,
+            overrides: vec![
+                ViolationOverride {
+                    syscall: UNKNOWN_SYSCALL,
+                    action: ViolationAction::Errno(libc::EPERM as u16),
+                },
+            ],
+        }).unwrap();
+        for architecture in ARCHITECTURES.iter() {
+            let filter = Filter::build(&profile, false, architecture);
+            assert_eq!(run(&filter, architecture.audit_arch, UNKNOWN_SYSCALL, [0; 6]),
+                       SECCOMP_RET_ERRNO | libc::EPERM as u32);
+        }
+    }
+
+    #[test]
+    fn audit_mode_notifies_the_supervisor() {
+        for architecture in ARCHITECTURES.iter() {
+            let filter = Filter::build(&read_profile(), true, architecture);
+            assert_eq!(run(&filter, architecture.audit_arch, UNKNOWN_SYSCALL, [0; 6]),
+                       SECCOMP_RET_USER_NOTIF);
+            assert_eq!(verdict(&filter, architecture, Syscall::Openat, [0; 6]),
+                       SECCOMP_RET_USER_NOTIF);
+            assert_eq!(verdict(&filter, architecture, Syscall::Read, [0; 6]), SECCOMP_RET_ALLOW);
+        }
+    }
+
+    #[test]
+    fn install_errors_report_the_stage_and_errno() {
+        // The kernel refuses an empty program. This still sets `PR_SET_NO_NEW_PRIVS` on the test
+        // process, which no other test minds.
+        let filter = Filter {
+            program: vec![],
+            architecture: &X86_64,
+            traps: false,
+        };
+        assert_eq!(filter.activate(),
+                   Err(Error::Os {
+                       stage: Stage::InstallSeccompFilter,
+                       path: None,
+                       errno: libc::EINVAL,
+                   }));
+    }
+}
@@ -0,0 +1,722 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Supervision of system calls that `seccomp-bpf` can't decide on by itself.
+//!
+//! A BPF filter only sees the raw arguments of a system call, so it can't tell which Unix socket
+//! a `connect` call names. For profiles that depend on that, the filter returns
+//! `SECCOMP_RET_USER_NOTIF` for `connect`, and the kernel suspends the call and notifies whoever
+//! holds the filter's listener descriptor. The sandboxed process hands that descriptor to the
+//! process that started it while it activates its sandbox, and a thread there reads the address
+//! out of the sandboxed process's memory and checks it against the profile. If the profile
+//! allows it, the supervisor takes a copy of the socket with `pidfd_getfd` and connects it to the
+//! address that it checked; otherwise it fails the call with `EACCES`.
+//!
+//! Letting the call continue instead would have the kernel read the address again, and another
+//! thread of the sandboxed process could swap it after it was checked. Connecting on the
+//! process's behalf also means that the socket is found outside of the `chroot` jail, so the jail
+//! doesn't need to contain it. Servers that look up the credentials of their peers see the
+//! supervisor's, though.
+//!
+//! In audit mode, the supervisor is also handed every system call that the filter would have
+//! refused. It records those, along with any file, program, or address that the profile wouldn't
+//! have allowed, and lets every call continue.
+
+use error::{self, Error, Stage};
+use platform::linux::audit::{AuditReport, Violation};
+use platform::linux::syscalls::Syscall;
+use profile::{AddressPattern, Operation, PathPattern, Profile};
+
+use libc::{self, c_int, c_uint, c_ulong, c_void, pid_t, sa_family_t};
+use std::cmp;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io;
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::os::unix::prelude::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// The environment variable that tells the sandboxed process which descriptor leads to its
+/// supervisor.
+const SUPERVISOR_FD_VARIABLE: &str = "GAOL_SUPERVISOR_FD";
+
+/// Makes the kernel notify the supervisor of a system call. Newer than `seccomp`'s other actions.
+pub const SECCOMP_RET_USER_NOTIF: u32 = 0x7fc0_0000;
+
+const SECCOMP_GET_ACTION_AVAIL: c_uint = 2;
+const SECCOMP_USER_NOTIF_FLAG_CONTINUE: u32 = 1 << 0;
+
+const SECCOMP_IOCTL_NOTIF_RECV: c_ulong = 0xc050_2100;
+const SECCOMP_IOCTL_NOTIF_SEND: c_ulong = 0xc018_2101;
+const SECCOMP_IOCTL_NOTIF_ID_VALID: c_ulong = 0x4008_2102;
+
+/// The longest path that the supervisor reads out of the sandboxed process's memory.
+const MAX_PATH_LENGTH: usize = 4096;
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct seccomp_data {
+    nr: c_int,
+    arch: u32,
+    instruction_pointer: u64,
+    args: [u64; 6],
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct seccomp_notif {
+    id: u64,
+    pid: u32,
+    flags: u32,
+    data: seccomp_data,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct seccomp_notif_resp {
+    id: u64,
+    val: i64,
+    error: i32,
+    flags: u32,
+}
+
+/// Returns true if the kernel can hand system calls to a supervisor.
+pub fn is_supported() -> bool {
+    let action = SECCOMP_RET_USER_NOTIF;
+    unsafe {
+        libc::syscall(libc::SYS_seccomp,
+                      SECCOMP_GET_ACTION_AVAIL,
+                      0 as c_uint,
+                      &action as *const u32) == 0
+    }
+}
+
+/// Returns true if the supervisor can make `connect` calls for the sandboxed process, which also
+/// takes `pidfd_getfd`.
+pub fn supports_connecting() -> bool {
+    is_supported() && unsafe {
+        // Where `pidfd_getfd` exists, it rejects the bad descriptor rather than failing with
+        // `ENOSYS`.
+        libc::syscall(libc::SYS_pidfd_getfd, -1 as c_int, 0 as c_int, 0 as c_uint) < 0 &&
+            errno() == libc::EBADF
+    }
+}
+
+/// Returns true if the profile can only be enforced with a supervisor's help.
+pub fn requires_supervisor(profile: &Profile) -> bool {
+    profile.allowed_operations().iter().any(|operation| {
+        matches!(*operation, Operation::NetworkOutbound(AddressPattern::LocalSocket(_)))
+    })
+}
+
+/// The pair of connected sockets over which a sandboxed process hands its listener descriptor to
+/// the process that started it.
+pub struct Channel {
+    supervisor_fd: c_int,
+    sandbox_fd: c_int,
+}
+
+impl Channel {
+    /// Creates the channel. Both ends are closed on `exec` until `inherit` is called.
+    pub fn new() -> Result<Channel,Error> {
+        let mut fds = [0, 0];
+        let result = unsafe {
+            libc::socketpair(libc::AF_UNIX,
+                             libc::SOCK_SEQPACKET | libc::SOCK_CLOEXEC,
+                             0,
+                             &mut fds[0])
+        };
+        if result != 0 {
+            return Err(error::last_os_error(Stage::Spawn, None))
+        }
+        Ok(Channel {
+            supervisor_fd: fds[0],
+            sandbox_fd: fds[1],
+        })
+    }
+
+    /// Returns the name and value of the environment variable that the sandboxed process needs in
+    /// order to find its end of the channel.
+    pub fn environment_variable(&self) -> (&'static str, String) {
+        (SUPERVISOR_FD_VARIABLE, self.sandbox_fd.to_string())
+    }
+
+    /// Lets the sandboxed process's end of the channel survive `exec`. Call this in the sandboxed
+    /// process, after forking.
+    pub fn inherit(&self) {
+        unsafe {
+            libc::fcntl(self.sandbox_fd, libc::F_SETFD, 0);
+        }
+    }
+
+    /// Closes the sandboxed process's end of the channel in this process and starts supervising
+    /// whatever process receives the listener descriptor from the other end.
+    pub fn supervise(self, profile: Profile) {
+        self.serve(move |listener, notification| answer(listener, notification, &profile))
+    }
+
+    /// Like `supervise`, but records what the process does that the profile wouldn't allow in
+    /// `report` and lets all of its system calls continue.
+    pub fn audit(self, profile: Profile, report: AuditReport) {
+        self.serve(move |listener, notification| {
+            audit(listener, notification, &profile, &report);
+            Answer::Continue
+        })
+    }
+
+    /// Starts a thread that waits for the listener descriptor and then answers notifications as
+    /// `answer` says.
+    fn serve<F>(mut self, answer: F)
+                where F: FnMut(c_int, &seccomp_notif) -> Answer + Send + 'static {
+        let supervisor_fd = self.supervisor_fd;
+        unsafe {
+            libc::close(self.sandbox_fd);
+        }
+        self.supervisor_fd = -1;
+        self.sandbox_fd = -1;
+        thread::spawn(move || {
+            if let Some(listener) = receive_listener(supervisor_fd) {
+                serve(listener, answer);
+                unsafe {
+                    libc::close(listener);
+                }
+            }
+            unsafe {
+                libc::close(supervisor_fd);
+            }
+        });
+    }
+}
+
+impl Drop for Channel {
+    fn drop(&mut self) {
+        for &fd in [self.supervisor_fd, self.sandbox_fd].iter() {
+            if fd >= 0 {
+                unsafe {
+                    libc::close(fd);
+                }
+            }
+        }
+    }
+}
+
+/// How the supervisor answers a system call.
+enum Answer {
+    /// Let the call go ahead as the process made it.
+    Continue,
+    /// Make the call return this value without running it.
+    Return(i64),
+    /// Make the call fail with this `errno` value.
+    Fail(c_int),
+}
+
+/// Finds the sandboxed process's end of the channel, if the sandbox needs a supervisor. This must
+/// be called before the environment is cleared.
+pub fn sandbox_channel(required: bool) -> Result<Option<c_int>,Error> {
+    if !required {
+        return Ok(None)
+    }
+    match env::var(SUPERVISOR_FD_VARIABLE).ok().and_then(|fd| fd.parse().ok()) {
+        Some(fd) => Ok(Some(fd)),
+        None => {
+            Err(Error::Platform {
+                stage: Stage::ContactSupervisor,
+                message: "the sandbox requires a supervisor, but this process wasn't started by \
+                          one".to_owned(),
+            })
+        }
+    }
+}
+
+/// Sends the filter's listener descriptor to the supervisor, then closes both descriptors.
+pub fn hand_over_listener(channel: c_int, listener: c_int) -> Result<(),Error> {
+    let mut byte = 0u8;
+    let mut iov = libc::iovec {
+        iov_base: &mut byte as *mut u8 as *mut c_void,
+        iov_len: 1,
+    };
+    let mut control = [0u64; 4];
+    let result = unsafe {
+        let mut message: libc::msghdr = mem::zeroed();
+        message.msg_iov = &mut iov;
+        message.msg_iovlen = 1;
+        message.msg_control = control.as_mut_ptr() as *mut c_void;
+        message.msg_controllen = libc::CMSG_SPACE(mem::size_of::<c_int>() as c_uint) as _;
+        let header = libc::CMSG_FIRSTHDR(&message);
+        (*header).cmsg_level = libc::SOL_SOCKET;
+        (*header).cmsg_type = libc::SCM_RIGHTS;
+        (*header).cmsg_len = libc::CMSG_LEN(mem::size_of::<c_int>() as c_uint) as _;
+        *(libc::CMSG_DATA(header) as *mut c_int) = listener;
+        libc::sendmsg(channel, &message, 0)
+    };
+    let error = error::last_os_error(Stage::ContactSupervisor, None);
+    unsafe {
+        libc::close(listener);
+        libc::close(channel);
+    }
+    if result == 1 {
+        Ok(())
+    } else {
+        Err(error)
+    }
+}
+
+/// Waits for the sandboxed process to send its listener descriptor. Returns `None` if it exits,
+/// or closes its end of the channel, without sending one.
+fn receive_listener(channel: c_int) -> Option<c_int> {
+    let mut byte = 0u8;
+    let mut iov = libc::iovec {
+        iov_base: &mut byte as *mut u8 as *mut c_void,
+        iov_len: 1,
+    };
+    let mut control = [0u64; 4];
+    unsafe {
+        let mut message: libc::msghdr = mem::zeroed();
+        message.msg_iov = &mut iov;
+        message.msg_iovlen = 1;
+        message.msg_control = control.as_mut_ptr() as *mut c_void;
+        message.msg_controllen = mem::size_of_val(&control) as _;
+        if libc::recvmsg(channel, &mut message, libc::MSG_CMSG_CLOEXEC) != 1 {
+            return None
+        }
+        let header = libc::CMSG_FIRSTHDR(&message);
+        if header.is_null() || (*header).cmsg_level != libc::SOL_SOCKET ||
+                (*header).cmsg_type != libc::SCM_RIGHTS {
+            return None
+        }
+        Some(*(libc::CMSG_DATA(header) as *const c_int))
+    }
+}
+
+/// Answers notifications until every process using the filter has exited.
+fn serve<F>(listener: c_int, mut answer: F) where F: FnMut(c_int, &seccomp_notif) -> Answer {
+    loop {
+        let mut pollfd = libc::pollfd {
+            fd: listener,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        if unsafe { libc::poll(&mut pollfd, 1, -1) } < 0 {
+            if errno() == libc::EINTR {
+                continue
+            }
+            return
+        }
+        if pollfd.revents & libc::POLLIN == 0 {
+            // The kernel signals a hangup once the filter has no processes left.
+            return
+        }
+
+        let mut notification: seccomp_notif = unsafe { mem::zeroed() };
+        if unsafe { libc::ioctl(listener, SECCOMP_IOCTL_NOTIF_RECV as _, &mut notification) } !=
+                0 {
+            // The process may have been killed before we got to its notification.
+            if errno() == libc::ENOENT || errno() == libc::EINTR {
+                continue
+            }
+            return
+        }
+
+        let mut response = seccomp_notif_resp {
+            id: notification.id,
+            val: 0,
+            error: 0,
+            flags: 0,
+        };
+        match answer(listener, &notification) {
+            Answer::Continue => response.flags = SECCOMP_USER_NOTIF_FLAG_CONTINUE,
+            Answer::Return(value) => response.val = value,
+            Answer::Fail(errno) => response.error = -errno,
+        }
+        if unsafe { libc::ioctl(listener, SECCOMP_IOCTL_NOTIF_SEND as _, &mut response) } != 0 &&
+                errno() != libc::ENOENT {
+            // If we can't answer, the process would wait forever; kill it instead.
+            unsafe {
+                libc::kill(notification.pid as pid_t, libc::SIGKILL);
+            }
+        }
+    }
+}
+
+/// Answers the system call described by `notification` according to the profile. Calls that the
+/// profile allows are made by the supervisor, with the arguments that it checked.
+fn answer(listener: c_int, notification: &seccomp_notif, profile: &Profile) -> Answer {
+    if Some(notification.data.nr as u32) != Syscall::Connect.number() {
+        return Answer::Fail(libc::EACCES)
+    }
+
+    let (address, length) = match read_address(notification.pid as pid_t,
+                                               notification.data.args[1],
+                                               notification.data.args[2]) {
+        Some(address) => address,
+        None => return Answer::Fail(libc::EACCES),
+    };
+    if !is_valid(listener, notification) || !connect_allowed(profile, &address, length) {
+        return Answer::Fail(libc::EACCES)
+    }
+    connect(listener, notification, &address, length)
+}
+
+/// Connects the socket named by the `connect` call described by `notification` to `address`,
+/// whose first `length` bytes are valid, and answers the call with the result.
+fn connect(listener: c_int, notification: &seccomp_notif, address: &libc::sockaddr_storage,
+           length: usize)
+           -> Answer {
+    let socket = match copy_descriptor(listener, notification, notification.data.args[0] as c_int) {
+        Ok(socket) => socket,
+        Err(errno) => return Answer::Fail(errno),
+    };
+    let result = unsafe {
+        libc::connect(socket,
+                      address as *const libc::sockaddr_storage as *const libc::sockaddr,
+                      length as libc::socklen_t)
+    };
+    let answer = if result == 0 {
+        Answer::Return(0)
+    } else {
+        Answer::Fail(errno())
+    };
+    unsafe {
+        libc::close(socket);
+    }
+    answer
+}
+
+/// Copies the descriptor `fd` of the process that made the call described by `notification` into
+/// this one. Returns the `errno` value explaining why, if it can't.
+fn copy_descriptor(listener: c_int, notification: &seccomp_notif, fd: c_int)
+                   -> Result<c_int,c_int> {
+    // The notification names the calling thread, but only whole processes have `pidfd`s.
+    let pid = match thread_group(notification.pid as pid_t) {
+        Some(pid) => pid,
+        None => return Err(libc::ESRCH),
+    };
+    let pidfd = unsafe {
+        libc::syscall(libc::SYS_pidfd_open, pid, 0 as c_uint) as c_int
+    };
+    if pidfd < 0 {
+        return Err(errno())
+    }
+    // Once we hold the `pidfd`, the PID can't be reused, so checking that the call is still
+    // waiting makes sure that we copy from the right process.
+    let result = if is_valid(listener, notification) {
+        let copy = unsafe {
+            libc::syscall(libc::SYS_pidfd_getfd, pidfd, fd, 0 as c_uint) as c_int
+        };
+        if copy < 0 {
+            Err(errno())
+        } else {
+            Ok(copy)
+        }
+    } else {
+        Err(libc::ESRCH)
+    };
+    unsafe {
+        libc::close(pidfd);
+    }
+    result
+}
+
+/// Returns the ID of the process that the thread `tid` belongs to.
+fn thread_group(tid: pid_t) -> Option<pid_t> {
+    let status = fs::read_to_string(format!("/proc/{}/status", tid)).ok()?;
+    let line = status.lines().find(|line| line.starts_with("Tgid:"))?;
+    line["Tgid:".len()..].trim().parse().ok()
+}
+
+/// Records in `report` whatever the system call described by `notification` does that the
+/// profile wouldn't allow.
+fn audit(listener: c_int, notification: &seccomp_notif, profile: &Profile, report: &AuditReport) {
+    let pid = notification.pid as pid_t;
+    let args = notification.data.args;
+    let number = Some(notification.data.nr as u32);
+    let violations = if number == Syscall::Open.number() {
+        opened_file_violations(pid, libc::AT_FDCWD, args[0], args[1] as c_int, profile)
+    } else if number == Syscall::Openat.number() {
+        opened_file_violations(pid, args[0] as c_int, args[1], args[2] as c_int, profile)
+    } else if number == Syscall::Execve.number() {
+        read_path(pid, libc::AT_FDCWD, args[0]).map(|path| {
+            let operation = Operation::Exec(PathPattern::Literal(path));
+            if file_allowed(profile, &operation) {
+                vec![]
+            } else {
+                vec![operation]
+            }
+        })
+    } else if number == Syscall::Connect.number() {
+        read_address(pid, args[1], args[2]).map(|(address, length)| {
+            if connect_allowed(profile, &address, length) {
+                vec![]
+            } else {
+                vec![Operation::NetworkOutbound(address_pattern(&address, length))]
+            }
+        })
+    } else {
+        report.record(Violation::Syscall(notification.data.nr as u32));
+        return
+    };
+
+    // If the arguments couldn't be read, the call is going to fail with `EFAULT` anyway.
+    if let Some(violations) = violations {
+        if is_valid(listener, notification) {
+            for operation in violations.into_iter() {
+                report.record(Violation::Operation(operation))
+            }
+        }
+    }
+}
+
+/// Returns the operations that opening the path at `address` with `flags` needs and the profile
+/// doesn't allow.
+fn opened_file_violations(pid: pid_t, directory: c_int, address: u64, flags: c_int,
+                          profile: &Profile)
+                          -> Option<Vec<Operation>> {
+    let path = read_path(pid, directory, address)?;
+    let mut operations = vec![];
+    if flags & libc::O_ACCMODE != libc::O_WRONLY {
+        operations.push(Operation::FileReadAll(PathPattern::Literal(path.clone())))
+    }
+    if flags & libc::O_ACCMODE != libc::O_RDONLY {
+        operations.push(Operation::FileWrite(PathPattern::Literal(path.clone())))
+    }
+    if flags & libc::O_CREAT != 0 && fs::symlink_metadata(&path).is_err() {
+        operations.push(Operation::FileCreateAndUnlink(PathPattern::Literal(path)))
+    }
+    Some(operations.into_iter().filter(|operation| !file_allowed(profile, operation)).collect())
+}
+
+/// Returns true if the profile allows `operation`, which names a single file.
+fn file_allowed(profile: &Profile, operation: &Operation) -> bool {
+    profile.allowed_operations().iter().any(|allowed| {
+        match (allowed, operation) {
+            (&Operation::FileReadAll(ref pattern),
+             &Operation::FileReadAll(PathPattern::Literal(ref path))) |
+            (&Operation::FileWrite(ref pattern),
+             &Operation::FileWrite(PathPattern::Literal(ref path))) |
+            (&Operation::FileCreateAndUnlink(ref pattern),
+             &Operation::FileCreateAndUnlink(PathPattern::Literal(ref path))) |
+            (&Operation::Exec(ref pattern), &Operation::Exec(PathPattern::Literal(ref path))) |
+            (&Operation::Exec(ref pattern),
+             &Operation::FileReadAll(PathPattern::Literal(ref path))) => pattern.matches(path),
+            (&Operation::DeviceAccess(ref device),
+             &Operation::FileReadAll(PathPattern::Literal(ref path))) |
+            (&Operation::DeviceAccess(ref device),
+             &Operation::FileWrite(PathPattern::Literal(ref path))) => device.path() == path,
+            (&Operation::ScratchDirectory { path: ref directory, .. },
+             &Operation::FileReadAll(PathPattern::Literal(ref path))) |
+            (&Operation::ScratchDirectory { path: ref directory, .. },
+             &Operation::FileWrite(PathPattern::Literal(ref path))) |
+            (&Operation::ScratchDirectory { path: ref directory, .. },
+             &Operation::FileCreateAndUnlink(PathPattern::Literal(ref path))) => {
+                path.starts_with(directory)
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Returns true if the process that made the call described by `notification` is still waiting
+/// for an answer.
+fn is_valid(listener: c_int, notification: &seccomp_notif) -> bool {
+    // The process could have died and its PID been reused while we read its memory, in which
+    // case we read someone else's.
+    let mut id = notification.id;
+    unsafe {
+        libc::ioctl(listener, SECCOMP_IOCTL_NOTIF_ID_VALID as _, &mut id) == 0
+    }
+}
+
+/// Reads the socket address of `length` bytes at `address` in process `pid`, returning it along
+/// with how many of its bytes are valid.
+fn read_address(pid: pid_t, address: u64, length: u64)
+                -> Option<(libc::sockaddr_storage, usize)> {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let length = cmp::min(length as usize, mem::size_of::<libc::sockaddr_storage>());
+    if read_memory(pid,
+                   address,
+                   &mut storage as *mut libc::sockaddr_storage as *mut c_void,
+                   length) {
+        Some((storage, length))
+    } else {
+        None
+    }
+}
+
+/// Reads the path at `address` in process `pid` and makes it absolute, treating it as relative to
+/// the descriptor `directory` the way `openat` does.
+fn read_path(pid: pid_t, directory: c_int, address: u64) -> Option<PathBuf> {
+    let mut bytes = vec![];
+    let mut address = address;
+    while bytes.len() < MAX_PATH_LENGTH {
+        // Never read across a page boundary, in case the next page isn't mapped.
+        let mut chunk = [0u8; 256];
+        let length = chunk.len() - (address % chunk.len() as u64) as usize;
+        if !read_memory(pid, address, chunk.as_mut_ptr() as *mut c_void, length) {
+            return None
+        }
+        match chunk[..length].iter().position(|byte| *byte == 0) {
+            Some(end) => {
+                bytes.extend_from_slice(&chunk[..end]);
+                break
+            }
+            None => bytes.extend_from_slice(&chunk[..length]),
+        }
+        address += length as u64;
+    }
+
+    let path = PathBuf::from(OsString::from_vec(bytes));
+    if path.is_absolute() {
+        return Some(path)
+    }
+    let base = if directory == libc::AT_FDCWD {
+        format!("/proc/{}/cwd", pid)
+    } else {
+        format!("/proc/{}/fd/{}", pid, directory)
+    };
+    fs::read_link(base).ok().map(|base| base.join(path))
+}
+
+/// Returns the narrowest pattern that matches `address`, whose first `length` bytes are valid.
+fn address_pattern(address: &libc::sockaddr_storage, length: usize) -> AddressPattern {
+    if let Some(path) = local_socket_path(address, length) {
+        return AddressPattern::LocalSocket(path)
+    }
+    match socket_address(address, length) {
+        Some(address) => AddressPattern::Tcp(address.port()),
+        None => AddressPattern::All,
+    }
+}
+
+/// Returns true if the profile allows connecting to `address`, whose first `length` bytes are
+/// valid.
+fn connect_allowed(profile: &Profile, address: &libc::sockaddr_storage, length: usize) -> bool {
+    if length < mem::size_of::<sa_family_t>() {
+        return false
+    }
+    match address.ss_family as c_int {
+        libc::AF_UNSPEC => true,
+        libc::AF_UNIX => {
+            // Abstract and relative addresses can't be checked against the profile's paths.
+            let path = match local_socket_path(address, length) {
+                Some(path) => path,
+                None => return false,
+            };
+            profile.allowed_operations().iter().any(|operation| {
+                match *operation {
+                    Operation::NetworkOutbound(AddressPattern::All) => true,
+                    Operation::NetworkOutbound(AddressPattern::LocalSocket(ref allowed)) => {
+                        *allowed == path
+                    }
+                    _ => false,
+                }
+            })
+        }
+        libc::AF_INET | libc::AF_INET6 => {
+            let address = match socket_address(address, length) {
+                Some(address) => address,
+                None => return false,
+            };
+            profile.allowed_operations().iter().any(|operation| {
+                match *operation {
+                    Operation::NetworkOutbound(ref pattern) => pattern.matches(&address),
+                    _ => false,
+                }
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Returns the path of a Unix socket address, if it is absolute.
+fn local_socket_path(address: &libc::sockaddr_storage, length: usize) -> Option<PathBuf> {
+    let offset = mem::size_of::<sa_family_t>();
+    if length < offset || address.ss_family as c_int != libc::AF_UNIX {
+        return None
+    }
+    let address = unsafe {
+        &*(address as *const libc::sockaddr_storage as *const libc::sockaddr_un)
+    };
+    let length = cmp::min(length - offset, address.sun_path.len());
+    let path: Vec<u8> = address.sun_path[..length].iter()
+                                                  .map(|byte| *byte as u8)
+                                                  .take_while(|byte| *byte != 0)
+                                                  .collect();
+    if path.first() == Some(&b'/') {
+        Some(Path::new(OsStr::from_bytes(&path)).to_path_buf())
+    } else {
+        None
+    }
+}
+
+/// Converts an IPv4 or IPv6 socket address into its Rust equivalent.
+fn socket_address(address: &libc::sockaddr_storage, length: usize) -> Option<SocketAddr> {
+    match address.ss_family as c_int {
+        libc::AF_INET if length >= mem::size_of::<libc::sockaddr_in>() => {
+            let address = unsafe {
+                &*(address as *const libc::sockaddr_storage as *const libc::sockaddr_in)
+            };
+            let ip = Ipv4Addr::from(u32::from_be(address.sin_addr.s_addr));
+            Some(SocketAddr::V4(SocketAddrV4::new(ip, u16::from_be(address.sin_port))))
+        }
+        libc::AF_INET6 if length >= mem::size_of::<libc::sockaddr_in6>() => {
+            let address = unsafe {
+                &*(address as *const libc::sockaddr_storage as *const libc::sockaddr_in6)
+            };
+            let ip = Ipv6Addr::from(address.sin6_addr.s6_addr);
+            Some(SocketAddr::V6(SocketAddrV6::new(ip,
+                                                  u16::from_be(address.sin6_port),
+                                                  address.sin6_flowinfo,
+                                                  address.sin6_scope_id)))
+        }
+        _ => None,
+    }
+}
+
+/// Copies `length` bytes at `address` in process `pid` into `buffer`.
+fn read_memory(pid: pid_t, address: u64, buffer: *mut c_void, length: usize) -> bool {
+    let local = libc::iovec {
+        iov_base: buffer,
+        iov_len: length,
+    };
+    let remote = libc::iovec {
+        iov_base: address as usize as *mut c_void,
+        iov_len: length,
+    };
+    let result = unsafe {
+        libc::process_vm_readv(pid, &local, 1, &remote, 1, 0)
+    };
+    result >= 0 && result as usize == length
+}
+
+/// Returns the calling thread's `errno` value.
+fn errno() -> c_int {
+    io::Error::last_os_error().raw_os_error().unwrap_or(libc::EIO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hand_over_listener;
+    use error::{Error, Stage};
+
+    use libc;
+
+    #[test]
+    fn errors_report_the_stage_and_errno() {
+        assert_eq!(hand_over_listener(-1, -1),
+                   Err(Error::Os {
+                       stage: Stage::ContactSupervisor,
+                       path: None,
+                       errno: libc::EBADF,
+                   }));
+    }
+}
@@ -0,0 +1,1314 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! System call numbers for each architecture that the `seccomp-bpf` filter supports.
+//!
+//! The filter is written in terms of `Syscall`, which names system calls by what they do, and
+//! looks their numbers up in the table for the architecture it is built for. Newer architectures
+//! such as aarch64 and riscv64 only have the `*at` forms of many calls and leave out legacy ones
+//! like `open`, `stat`, and `poll`; those have no number there, and the filter simply doesn't
+//! mention them.
+//!
+//! 32-bit x86 has its own numbering. Its table maps calls to the large-file, 32-bit user ID, and
+//! 64-bit time versions that the C library uses there, and it also has the `socketcall` and `ipc`
+//! multiplexers that older C libraries send socket and System V IPC calls through. 32-bit ARM and
+//! PowerPC are mapped the same way; PowerPC keeps the multiplexers even in its 64-bit ABIs, while
+//! ARM's EABI never had them.
+//!
+//! The tables are written out by hand rather than taken from `libc`, so that filters for every
+//! architecture can be built and checked on any of them.
+
+/// A flag set in the architecture number for all 64-bit architectures.
+const __AUDIT_ARCH_64BIT: u32 = 0x8000_0000;
+/// A flag set in the architecture number for all little-endian architectures.
+const __AUDIT_ARCH_LE: u32 = 0x4000_0000;
+
+const EM_386: u32 = 3;
+const EM_PPC: u32 = 20;
+const EM_PPC64: u32 = 21;
+const EM_ARM: u32 = 40;
+const EM_X86_64: u32 = 62;
+const EM_AARCH64: u32 = 183;
+const EM_RISCV: u32 = 243;
+
+/// The architecture number for 32-bit x86.
+const AUDIT_ARCH_I386: u32 = EM_386 | __AUDIT_ARCH_LE;
+/// The architecture number for x86-64.
+const AUDIT_ARCH_X86_64: u32 = EM_X86_64 | __AUDIT_ARCH_64BIT | __AUDIT_ARCH_LE;
+/// The architecture number for 32-bit ARM.
+const AUDIT_ARCH_ARM: u32 = EM_ARM | __AUDIT_ARCH_LE;
+/// The architecture number for ARM 64-bit.
+const AUDIT_ARCH_AARCH64: u32 = EM_AARCH64 | __AUDIT_ARCH_64BIT | __AUDIT_ARCH_LE;
+/// The architecture number for 32-bit PowerPC, which is big-endian.
+const AUDIT_ARCH_PPC: u32 = EM_PPC;
+/// The architecture number for big-endian 64-bit PowerPC.
+const AUDIT_ARCH_PPC64: u32 = EM_PPC64 | __AUDIT_ARCH_64BIT;
+/// The architecture number for little-endian 64-bit PowerPC.
+const AUDIT_ARCH_PPC64LE: u32 = EM_PPC64 | __AUDIT_ARCH_64BIT | __AUDIT_ARCH_LE;
+/// The architecture number for RISC-V 64-bit.
+const AUDIT_ARCH_RISCV64: u32 = EM_RISCV | __AUDIT_ARCH_64BIT | __AUDIT_ARCH_LE;
+
+/// The bit that x32 programs set in their system call numbers on x86-64.
+const __X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+/// The system calls that the filter refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Syscall {
+    Accept,
+    Accept4,
+    Access,
+    ArchPrctl,
+    Bind,
+    Brk,
+    ClockGetres,
+    ClockGetresTime64,
+    ClockGettime,
+    ClockGettime64,
+    ClockNanosleep,
+    ClockNanosleepTime64,
+    Clone,
+    Clone3,
+    Close,
+    Connect,
+    Dup2,
+    Dup3,
+    Execve,
+    Exit,
+    ExitGroup,
+    Faccessat,
+    Faccessat2,
+    Fcntl,
+    Fdatasync,
+    Fork,
+    Fstat,
+    Fstatfs,
+    Fsync,
+    Ftruncate,
+    Futex,
+    FutexTime64,
+    Getdents64,
+    Getpid,
+    Getrandom,
+    Getsockname,
+    Getsockopt,
+    Gettid,
+    Getuid,
+    Ioctl,
+    Ipc,
+    Kill,
+    Listen,
+    Lseek,
+    Lstat,
+    Madvise,
+    Mkdir,
+    Mkdirat,
+    Mmap,
+    Mprotect,
+    Mremap,
+    Munmap,
+    Nanosleep,
+    Newfstatat,
+    Open,
+    Openat,
+    Pipe2,
+    Poll,
+    Ppoll,
+    PpollTime64,
+    Pread64,
+    Prlimit64,
+    Pwrite64,
+    Read,
+    Readlink,
+    Readlinkat,
+    Readv,
+    Recvfrom,
+    Recvmsg,
+    Rename,
+    Renameat,
+    Renameat2,
+    Rmdir,
+    Rseq,
+    RtSigaction,
+    RtSigprocmask,
+    RtSigreturn,
+    SchedGetaffinity,
+    SchedYield,
+    Sendmmsg,
+    Sendmsg,
+    Sendto,
+    SetRobustList,
+    SetThreadArea,
+    SetTidAddress,
+    Setsockopt,
+    Sigaltstack,
+    Socket,
+    Socketcall,
+    Stat,
+    Statfs,
+    Statx,
+    Tgkill,
+    Tkill,
+    Truncate,
+    Unlink,
+    Unlinkat,
+    Vfork,
+    Wait4,
+    Waitid,
+    Write,
+    Writev,
+}
+
+impl Syscall {
+    /// Returns the number of this system call on the architecture this crate was built for, or
+    /// `None` if it doesn't have one.
+    pub fn number(self) -> Option<u32> {
+        HOST.number(self)
+    }
+}
+
+/// An architecture's system call ABI, as `seccomp` sees it.
+pub struct Architecture {
+    /// The `AUDIT_ARCH_*` value that the kernel reports for system calls made with this ABI.
+    pub audit_arch: u32,
+    /// A bit that selects another ABI when it is set in a system call number. The x32 ABI does
+    /// this on x86-64, and reports the same architecture number as x86-64 itself.
+    pub abi_syscall_bit: Option<u32>,
+    numbers: fn(Syscall) -> Option<u32>,
+}
+
+impl Architecture {
+    /// Returns the number of `syscall` on this architecture, or `None` if it doesn't have one.
+    pub fn number(&self, syscall: Syscall) -> Option<u32> {
+        (self.numbers)(syscall)
+    }
+
+    /// Returns true if this ABI stores the low half of each 64-bit system call argument first.
+    pub fn is_little_endian(&self) -> bool {
+        self.audit_arch & __AUDIT_ARCH_LE != 0
+    }
+}
+
+pub static X86: Architecture = Architecture {
+    audit_arch: AUDIT_ARCH_I386,
+    abi_syscall_bit: None,
+    numbers: x86_number,
+};
+
+pub static X86_64: Architecture = Architecture {
+    audit_arch: AUDIT_ARCH_X86_64,
+    abi_syscall_bit: Some(__X32_SYSCALL_BIT),
+    numbers: x86_64_number,
+};
+
+pub static ARM: Architecture = Architecture {
+    audit_arch: AUDIT_ARCH_ARM,
+    abi_syscall_bit: None,
+    numbers: arm_number,
+};
+
+pub static AARCH64: Architecture = Architecture {
+    audit_arch: AUDIT_ARCH_AARCH64,
+    abi_syscall_bit: None,
+    numbers: aarch64_number,
+};
+
+pub static POWERPC: Architecture = Architecture {
+    audit_arch: AUDIT_ARCH_PPC,
+    abi_syscall_bit: None,
+    numbers: powerpc_number,
+};
+
+pub static POWERPC64: Architecture = Architecture {
+    audit_arch: AUDIT_ARCH_PPC64,
+    abi_syscall_bit: None,
+    numbers: powerpc64_number,
+};
+
+pub static POWERPC64LE: Architecture = Architecture {
+    audit_arch: AUDIT_ARCH_PPC64LE,
+    abi_syscall_bit: None,
+    numbers: powerpc64_number,
+};
+
+pub static RISCV64: Architecture = Architecture {
+    audit_arch: AUDIT_ARCH_RISCV64,
+    abi_syscall_bit: None,
+    numbers: generic_number,
+};
+
+/// The architecture this crate was built for.
+#[cfg(target_arch="x86")]
+pub static HOST: &Architecture = &X86;
+/// The architecture this crate was built for.
+#[cfg(target_arch="x86_64")]
+pub static HOST: &Architecture = &X86_64;
+/// The architecture this crate was built for.
+#[cfg(target_arch="arm")]
+pub static HOST: &Architecture = &ARM;
+/// The architecture this crate was built for.
+#[cfg(target_arch="aarch64")]
+pub static HOST: &Architecture = &AARCH64;
+/// The architecture this crate was built for.
+#[cfg(target_arch="powerpc")]
+pub static HOST: &Architecture = &POWERPC;
+/// The architecture this crate was built for.
+#[cfg(all(target_arch="powerpc64", target_endian="big"))]
+pub static HOST: &Architecture = &POWERPC64;
+/// The architecture this crate was built for.
+#[cfg(all(target_arch="powerpc64", target_endian="little"))]
+pub static HOST: &Architecture = &POWERPC64LE;
+/// The architecture this crate was built for.
+#[cfg(target_arch="riscv64")]
+pub static HOST: &Architecture = &RISCV64;
+
+/// The numbers for 32-bit x86, where some calls are replaced by versions that take 64-bit file
+/// offsets, user IDs, or times. There is no separate `accept`, only `accept4` and `socketcall`.
+fn x86_number(syscall: Syscall) -> Option<u32> {
+    match syscall {
+        Syscall::Accept4 => Some(364),
+        Syscall::Access => Some(33),
+        Syscall::ArchPrctl => Some(384),
+        Syscall::Bind => Some(361),
+        Syscall::Brk => Some(45),
+        Syscall::ClockGetres => Some(266),
+        Syscall::ClockGetresTime64 => Some(406),
+        Syscall::ClockGettime => Some(265),
+        Syscall::ClockGettime64 => Some(403),
+        Syscall::ClockNanosleep => Some(267),
+        Syscall::ClockNanosleepTime64 => Some(407),
+        Syscall::Clone => Some(120),
+        Syscall::Clone3 => Some(435),
+        Syscall::Close => Some(6),
+        Syscall::Connect => Some(362),
+        Syscall::Dup2 => Some(63),
+        Syscall::Dup3 => Some(330),
+        Syscall::Execve => Some(11),
+        Syscall::Exit => Some(1),
+        Syscall::ExitGroup => Some(252),
+        Syscall::Faccessat => Some(307),
+        Syscall::Faccessat2 => Some(439),
+        Syscall::Fcntl => Some(221), // `fcntl64`
+        Syscall::Fdatasync => Some(148),
+        Syscall::Fork => Some(2),
+        Syscall::Fstat => Some(197), // `fstat64`
+        Syscall::Fstatfs => Some(269), // `fstatfs64`
+        Syscall::Fsync => Some(118),
+        Syscall::Ftruncate => Some(194), // `ftruncate64`
+        Syscall::Futex => Some(240),
+        Syscall::FutexTime64 => Some(422),
+        Syscall::Getdents64 => Some(220),
+        Syscall::Getpid => Some(20),
+        Syscall::Getrandom => Some(355),
+        Syscall::Getsockname => Some(367),
+        Syscall::Getsockopt => Some(365),
+        Syscall::Gettid => Some(224),
+        Syscall::Getuid => Some(199), // `getuid32`
+        Syscall::Ioctl => Some(54),
+        Syscall::Ipc => Some(117),
+        Syscall::Kill => Some(37),
+        Syscall::Listen => Some(363),
+        Syscall::Lseek => Some(140), // `_llseek`
+        Syscall::Lstat => Some(196), // `lstat64`
+        Syscall::Madvise => Some(219),
+        Syscall::Mkdir => Some(39),
+        Syscall::Mkdirat => Some(296),
+        Syscall::Mmap => Some(192), // `mmap2`
+        Syscall::Mprotect => Some(125),
+        Syscall::Mremap => Some(163),
+        Syscall::Munmap => Some(91),
+        Syscall::Nanosleep => Some(162),
+        Syscall::Newfstatat => Some(300), // `fstatat64`
+        Syscall::Open => Some(5),
+        Syscall::Openat => Some(295),
+        Syscall::Pipe2 => Some(331),
+        Syscall::Poll => Some(168),
+        Syscall::Ppoll => Some(309),
+        Syscall::PpollTime64 => Some(414),
+        Syscall::Pread64 => Some(180),
+        Syscall::Prlimit64 => Some(340),
+        Syscall::Pwrite64 => Some(181),
+        Syscall::Read => Some(3),
+        Syscall::Readlink => Some(85),
+        Syscall::Readlinkat => Some(305),
+        Syscall::Readv => Some(145),
+        Syscall::Recvfrom => Some(371),
+        Syscall::Recvmsg => Some(372),
+        Syscall::Rename => Some(38),
+        Syscall::Renameat => Some(302),
+        Syscall::Renameat2 => Some(353),
+        Syscall::Rmdir => Some(40),
+        Syscall::Rseq => Some(386),
+        Syscall::RtSigaction => Some(174),
+        Syscall::RtSigprocmask => Some(175),
+        Syscall::RtSigreturn => Some(173),
+        Syscall::SchedGetaffinity => Some(242),
+        Syscall::SchedYield => Some(158),
+        Syscall::Sendmmsg => Some(345),
+        Syscall::Sendmsg => Some(370),
+        Syscall::Sendto => Some(369),
+        Syscall::SetRobustList => Some(311),
+        Syscall::SetThreadArea => Some(243),
+        Syscall::SetTidAddress => Some(258),
+        Syscall::Setsockopt => Some(366),
+        Syscall::Sigaltstack => Some(186),
+        Syscall::Socket => Some(359),
+        Syscall::Socketcall => Some(102),
+        Syscall::Stat => Some(195), // `stat64`
+        Syscall::Statfs => Some(268), // `statfs64`
+        Syscall::Statx => Some(383),
+        Syscall::Tgkill => Some(270),
+        Syscall::Tkill => Some(238),
+        Syscall::Truncate => Some(193), // `truncate64`
+        Syscall::Unlink => Some(10),
+        Syscall::Unlinkat => Some(301),
+        Syscall::Vfork => Some(190),
+        Syscall::Wait4 => Some(114),
+        Syscall::Waitid => Some(284),
+        Syscall::Write => Some(4),
+        Syscall::Writev => Some(146),
+        Syscall::Accept => None,
+    }
+}
+
+fn x86_64_number(syscall: Syscall) -> Option<u32> {
+    match syscall {
+        Syscall::Accept => Some(43),
+        Syscall::Accept4 => Some(288),
+        Syscall::Access => Some(21),
+        Syscall::ArchPrctl => Some(158),
+        Syscall::Bind => Some(49),
+        Syscall::Brk => Some(12),
+        Syscall::ClockGetres => Some(229),
+        Syscall::ClockGettime => Some(228),
+        Syscall::ClockNanosleep => Some(230),
+        Syscall::Clone => Some(56),
+        Syscall::Clone3 => Some(435),
+        Syscall::Close => Some(3),
+        Syscall::Connect => Some(42),
+        Syscall::Dup2 => Some(33),
+        Syscall::Dup3 => Some(292),
+        Syscall::Execve => Some(59),
+        Syscall::Exit => Some(60),
+        Syscall::ExitGroup => Some(231),
+        Syscall::Faccessat => Some(269),
+        Syscall::Faccessat2 => Some(439),
+        Syscall::Fcntl => Some(72),
+        Syscall::Fdatasync => Some(75),
+        Syscall::Fork => Some(57),
+        Syscall::Fstat => Some(5),
+        Syscall::Fstatfs => Some(138),
+        Syscall::Fsync => Some(74),
+        Syscall::Ftruncate => Some(77),
+        Syscall::Futex => Some(202),
+        Syscall::Getdents64 => Some(217),
+        Syscall::Getpid => Some(39),
+        Syscall::Getrandom => Some(318),
+        Syscall::Getsockname => Some(51),
+        Syscall::Getsockopt => Some(55),
+        Syscall::Gettid => Some(186),
+        Syscall::Getuid => Some(102),
+        Syscall::Ioctl => Some(16),
+        Syscall::Kill => Some(62),
+        Syscall::Listen => Some(50),
+        Syscall::Lseek => Some(8),
+        Syscall::Lstat => Some(6),
+        Syscall::Madvise => Some(28),
+        Syscall::Mkdir => Some(83),
+        Syscall::Mkdirat => Some(258),
+        Syscall::Mmap => Some(9),
+        Syscall::Mprotect => Some(10),
+        Syscall::Mremap => Some(25),
+        Syscall::Munmap => Some(11),
+        Syscall::Nanosleep => Some(35),
+        Syscall::Newfstatat => Some(262),
+        Syscall::Open => Some(2),
+        Syscall::Openat => Some(257),
+        Syscall::Pipe2 => Some(293),
+        Syscall::Poll => Some(7),
+        Syscall::Ppoll => Some(271),
+        Syscall::Pread64 => Some(17),
+        Syscall::Prlimit64 => Some(302),
+        Syscall::Pwrite64 => Some(18),
+        Syscall::Read => Some(0),
+        Syscall::Readlink => Some(89),
+        Syscall::Readlinkat => Some(267),
+        Syscall::Readv => Some(19),
+        Syscall::Recvfrom => Some(45),
+        Syscall::Recvmsg => Some(47),
+        Syscall::Rename => Some(82),
+        Syscall::Renameat => Some(264),
+        Syscall::Renameat2 => Some(316),
+        Syscall::Rmdir => Some(84),
+        Syscall::Rseq => Some(334),
+        Syscall::RtSigaction => Some(13),
+        Syscall::RtSigprocmask => Some(14),
+        Syscall::RtSigreturn => Some(15),
+        Syscall::SchedGetaffinity => Some(204),
+        Syscall::SchedYield => Some(24),
+        Syscall::Sendmmsg => Some(307),
+        Syscall::Sendmsg => Some(46),
+        Syscall::Sendto => Some(44),
+        Syscall::SetRobustList => Some(273),
+        Syscall::SetThreadArea => Some(205),
+        Syscall::SetTidAddress => Some(218),
+        Syscall::Setsockopt => Some(54),
+        Syscall::Sigaltstack => Some(131),
+        Syscall::Socket => Some(41),
+        Syscall::Stat => Some(4),
+        Syscall::Statfs => Some(137),
+        Syscall::Statx => Some(332),
+        Syscall::Tgkill => Some(234),
+        Syscall::Tkill => Some(200),
+        Syscall::Truncate => Some(76),
+        Syscall::Unlink => Some(87),
+        Syscall::Unlinkat => Some(263),
+        Syscall::Vfork => Some(58),
+        Syscall::Wait4 => Some(61),
+        Syscall::Waitid => Some(247),
+        Syscall::Write => Some(1),
+        Syscall::Writev => Some(20),
+        Syscall::ClockGetresTime64 | Syscall::ClockGettime64 | Syscall::ClockNanosleepTime64 |
+        Syscall::FutexTime64 | Syscall::Ipc | Syscall::PpollTime64 | Syscall::Socketcall => None,
+    }
+}
+
+/// The numbers for 32-bit ARM's EABI, which uses the same kinds of replacements as 32-bit x86 but
+/// has no `socketcall` or `ipc`.
+fn arm_number(syscall: Syscall) -> Option<u32> {
+    match syscall {
+        Syscall::Accept => Some(285),
+        Syscall::Accept4 => Some(366),
+        Syscall::Access => Some(33),
+        Syscall::Bind => Some(282),
+        Syscall::Brk => Some(45),
+        Syscall::ClockGetres => Some(264),
+        Syscall::ClockGetresTime64 => Some(406),
+        Syscall::ClockGettime => Some(263),
+        Syscall::ClockGettime64 => Some(403),
+        Syscall::ClockNanosleep => Some(265),
+        Syscall::ClockNanosleepTime64 => Some(407),
+        Syscall::Clone => Some(120),
+        Syscall::Clone3 => Some(435),
+        Syscall::Close => Some(6),
+        Syscall::Connect => Some(283),
+        Syscall::Dup2 => Some(63),
+        Syscall::Dup3 => Some(358),
+        Syscall::Execve => Some(11),
+        Syscall::Exit => Some(1),
+        Syscall::ExitGroup => Some(248),
+        Syscall::Faccessat => Some(334),
+        Syscall::Faccessat2 => Some(439),
+        Syscall::Fcntl => Some(221), // `fcntl64`
+        Syscall::Fdatasync => Some(148),
+        Syscall::Fork => Some(2),
+        Syscall::Fstat => Some(197), // `fstat64`
+        Syscall::Fstatfs => Some(267), // `fstatfs64`
+        Syscall::Fsync => Some(118),
+        Syscall::Ftruncate => Some(194), // `ftruncate64`
+        Syscall::Futex => Some(240),
+        Syscall::FutexTime64 => Some(422),
+        Syscall::Getdents64 => Some(217),
+        Syscall::Getpid => Some(20),
+        Syscall::Getrandom => Some(384),
+        Syscall::Getsockname => Some(286),
+        Syscall::Getsockopt => Some(295),
+        Syscall::Gettid => Some(224),
+        Syscall::Getuid => Some(199), // `getuid32`
+        Syscall::Ioctl => Some(54),
+        Syscall::Kill => Some(37),
+        Syscall::Listen => Some(284),
+        Syscall::Lseek => Some(140), // `_llseek`
+        Syscall::Lstat => Some(196), // `lstat64`
+        Syscall::Madvise => Some(220),
+        Syscall::Mkdir => Some(39),
+        Syscall::Mkdirat => Some(323),
+        Syscall::Mmap => Some(192), // `mmap2`
+        Syscall::Mprotect => Some(125),
+        Syscall::Mremap => Some(163),
+        Syscall::Munmap => Some(91),
+        Syscall::Nanosleep => Some(162),
+        Syscall::Newfstatat => Some(327), // `fstatat64`
+        Syscall::Open => Some(5),
+        Syscall::Openat => Some(322),
+        Syscall::Pipe2 => Some(359),
+        Syscall::Poll => Some(168),
+        Syscall::Ppoll => Some(336),
+        Syscall::PpollTime64 => Some(414),
+        Syscall::Pread64 => Some(180),
+        Syscall::Prlimit64 => Some(369),
+        Syscall::Pwrite64 => Some(181),
+        Syscall::Read => Some(3),
+        Syscall::Readlink => Some(85),
+        Syscall::Readlinkat => Some(332),
+        Syscall::Readv => Some(145),
+        Syscall::Recvfrom => Some(292),
+        Syscall::Recvmsg => Some(297),
+        Syscall::Rename => Some(38),
+        Syscall::Renameat => Some(329),
+        Syscall::Renameat2 => Some(382),
+        Syscall::Rmdir => Some(40),
+        Syscall::Rseq => Some(398),
+        Syscall::RtSigaction => Some(174),
+        Syscall::RtSigprocmask => Some(175),
+        Syscall::RtSigreturn => Some(173),
+        Syscall::SchedGetaffinity => Some(242),
+        Syscall::SchedYield => Some(158),
+        Syscall::Sendmmsg => Some(374),
+        Syscall::Sendmsg => Some(296),
+        Syscall::Sendto => Some(290),
+        Syscall::SetRobustList => Some(338),
+        Syscall::SetTidAddress => Some(256),
+        Syscall::Setsockopt => Some(294),
+        Syscall::Sigaltstack => Some(186),
+        Syscall::Socket => Some(281),
+        Syscall::Stat => Some(195), // `stat64`
+        Syscall::Statfs => Some(266), // `statfs64`
+        Syscall::Statx => Some(397),
+        Syscall::Tgkill => Some(268),
+        Syscall::Tkill => Some(238),
+        Syscall::Truncate => Some(193), // `truncate64`
+        Syscall::Unlink => Some(10),
+        Syscall::Unlinkat => Some(328),
+        Syscall::Vfork => Some(190),
+        Syscall::Wait4 => Some(114),
+        Syscall::Waitid => Some(280),
+        Syscall::Write => Some(4),
+        Syscall::Writev => Some(146),
+        Syscall::ArchPrctl | Syscall::Ipc | Syscall::SetThreadArea | Syscall::Socketcall => None,
+    }
+}
+
+/// The numbers for 32-bit PowerPC. User IDs were always 32 bits wide here, so there is no
+/// `getuid32`.
+fn powerpc_number(syscall: Syscall) -> Option<u32> {
+    match syscall {
+        Syscall::Accept => Some(330),
+        Syscall::Accept4 => Some(344),
+        Syscall::Access => Some(33),
+        Syscall::Bind => Some(327),
+        Syscall::Brk => Some(45),
+        Syscall::ClockGetres => Some(247),
+        Syscall::ClockGetresTime64 => Some(406),
+        Syscall::ClockGettime => Some(246),
+        Syscall::ClockGettime64 => Some(403),
+        Syscall::ClockNanosleep => Some(248),
+        Syscall::ClockNanosleepTime64 => Some(407),
+        Syscall::Clone => Some(120),
+        Syscall::Clone3 => Some(435),
+        Syscall::Close => Some(6),
+        Syscall::Connect => Some(328),
+        Syscall::Dup2 => Some(63),
+        Syscall::Dup3 => Some(316),
+        Syscall::Execve => Some(11),
+        Syscall::Exit => Some(1),
+        Syscall::ExitGroup => Some(234),
+        Syscall::Faccessat => Some(298),
+        Syscall::Faccessat2 => Some(439),
+        Syscall::Fcntl => Some(204), // `fcntl64`
+        Syscall::Fdatasync => Some(148),
+        Syscall::Fork => Some(2),
+        Syscall::Fstat => Some(197), // `fstat64`
+        Syscall::Fstatfs => Some(253), // `fstatfs64`
+        Syscall::Fsync => Some(118),
+        Syscall::Ftruncate => Some(194), // `ftruncate64`
+        Syscall::Futex => Some(221),
+        Syscall::FutexTime64 => Some(422),
+        Syscall::Getdents64 => Some(202),
+        Syscall::Getpid => Some(20),
+        Syscall::Getrandom => Some(359),
+        Syscall::Getsockname => Some(331),
+        Syscall::Getsockopt => Some(340),
+        Syscall::Gettid => Some(207),
+        Syscall::Getuid => Some(24),
+        Syscall::Ioctl => Some(54),
+        Syscall::Ipc => Some(117),
+        Syscall::Kill => Some(37),
+        Syscall::Listen => Some(329),
+        Syscall::Lseek => Some(140), // `_llseek`
+        Syscall::Lstat => Some(196), // `lstat64`
+        Syscall::Madvise => Some(205),
+        Syscall::Mkdir => Some(39),
+        Syscall::Mkdirat => Some(287),
+        Syscall::Mmap => Some(192), // `mmap2`
+        Syscall::Mprotect => Some(125),
+        Syscall::Mremap => Some(163),
+        Syscall::Munmap => Some(91),
+        Syscall::Nanosleep => Some(162),
+        Syscall::Newfstatat => Some(291), // `fstatat64`
+        Syscall::Open => Some(5),
+        Syscall::Openat => Some(286),
+        Syscall::Pipe2 => Some(317),
+        Syscall::Poll => Some(167),
+        Syscall::Ppoll => Some(281),
+        Syscall::PpollTime64 => Some(414),
+        Syscall::Pread64 => Some(179),
+        Syscall::Prlimit64 => Some(325),
+        Syscall::Pwrite64 => Some(180),
+        Syscall::Read => Some(3),
+        Syscall::Readlink => Some(85),
+        Syscall::Readlinkat => Some(296),
+        Syscall::Readv => Some(145),
+        Syscall::Recvfrom => Some(337),
+        Syscall::Recvmsg => Some(342),
+        Syscall::Rename => Some(38),
+        Syscall::Renameat => Some(293),
+        Syscall::Renameat2 => Some(357),
+        Syscall::Rmdir => Some(40),
+        Syscall::Rseq => Some(387),
+        Syscall::RtSigaction => Some(173),
+        Syscall::RtSigprocmask => Some(174),
+        Syscall::RtSigreturn => Some(172),
+        Syscall::SchedGetaffinity => Some(223),
+        Syscall::SchedYield => Some(158),
+        Syscall::Sendmmsg => Some(349),
+        Syscall::Sendmsg => Some(341),
+        Syscall::Sendto => Some(335),
+        Syscall::SetRobustList => Some(300),
+        Syscall::SetTidAddress => Some(232),
+        Syscall::Setsockopt => Some(339),
+        Syscall::Sigaltstack => Some(185),
+        Syscall::Socket => Some(326),
+        Syscall::Socketcall => Some(102),
+        Syscall::Stat => Some(195), // `stat64`
+        Syscall::Statfs => Some(252), // `statfs64`
+        Syscall::Statx => Some(383),
+        Syscall::Tgkill => Some(250),
+        Syscall::Tkill => Some(208),
+        Syscall::Truncate => Some(193), // `truncate64`
+        Syscall::Unlink => Some(10),
+        Syscall::Unlinkat => Some(292),
+        Syscall::Vfork => Some(189),
+        Syscall::Wait4 => Some(114),
+        Syscall::Waitid => Some(272),
+        Syscall::Write => Some(4),
+        Syscall::Writev => Some(146),
+        Syscall::ArchPrctl | Syscall::SetThreadArea => None,
+    }
+}
+
+/// The numbers for 64-bit PowerPC, in either byte order. Most of them are shared with 32-bit
+/// PowerPC.
+fn powerpc64_number(syscall: Syscall) -> Option<u32> {
+    match syscall {
+        Syscall::Accept => Some(330),
+        Syscall::Accept4 => Some(344),
+        Syscall::Access => Some(33),
+        Syscall::Bind => Some(327),
+        Syscall::Brk => Some(45),
+        Syscall::ClockGetres => Some(247),
+        Syscall::ClockGettime => Some(246),
+        Syscall::ClockNanosleep => Some(248),
+        Syscall::Clone => Some(120),
+        Syscall::Clone3 => Some(435),
+        Syscall::Close => Some(6),
+        Syscall::Connect => Some(328),
+        Syscall::Dup2 => Some(63),
+        Syscall::Dup3 => Some(316),
+        Syscall::Execve => Some(11),
+        Syscall::Exit => Some(1),
+        Syscall::ExitGroup => Some(234),
+        Syscall::Faccessat => Some(298),
+        Syscall::Faccessat2 => Some(439),
+        Syscall::Fcntl => Some(55),
+        Syscall::Fdatasync => Some(148),
+        Syscall::Fork => Some(2),
+        Syscall::Fstat => Some(108),
+        Syscall::Fstatfs => Some(100),
+        Syscall::Fsync => Some(118),
+        Syscall::Ftruncate => Some(93),
+        Syscall::Futex => Some(221),
+        Syscall::Getdents64 => Some(202),
+        Syscall::Getpid => Some(20),
+        Syscall::Getrandom => Some(359),
+        Syscall::Getsockname => Some(331),
+        Syscall::Getsockopt => Some(340),
+        Syscall::Gettid => Some(207),
+        Syscall::Getuid => Some(24),
+        Syscall::Ioctl => Some(54),
+        Syscall::Ipc => Some(117),
+        Syscall::Kill => Some(37),
+        Syscall::Listen => Some(329),
+        Syscall::Lseek => Some(19),
+        Syscall::Lstat => Some(107),
+        Syscall::Madvise => Some(205),
+        Syscall::Mkdir => Some(39),
+        Syscall::Mkdirat => Some(287),
+        Syscall::Mmap => Some(90),
+        Syscall::Mprotect => Some(125),
+        Syscall::Mremap => Some(163),
+        Syscall::Munmap => Some(91),
+        Syscall::Nanosleep => Some(162),
+        Syscall::Newfstatat => Some(291),
+        Syscall::Open => Some(5),
+        Syscall::Openat => Some(286),
+        Syscall::Pipe2 => Some(317),
+        Syscall::Poll => Some(167),
+        Syscall::Ppoll => Some(281),
+        Syscall::Pread64 => Some(179),
+        Syscall::Prlimit64 => Some(325),
+        Syscall::Pwrite64 => Some(180),
+        Syscall::Read => Some(3),
+        Syscall::Readlink => Some(85),
+        Syscall::Readlinkat => Some(296),
+        Syscall::Readv => Some(145),
+        Syscall::Recvfrom => Some(337),
+        Syscall::Recvmsg => Some(342),
+        Syscall::Rename => Some(38),
+        Syscall::Renameat => Some(293),
+        Syscall::Renameat2 => Some(357),
+        Syscall::Rmdir => Some(40),
+        Syscall::Rseq => Some(387),
+        Syscall::RtSigaction => Some(173),
+        Syscall::RtSigprocmask => Some(174),
+        Syscall::RtSigreturn => Some(172),
+        Syscall::SchedGetaffinity => Some(223),
+        Syscall::SchedYield => Some(158),
+        Syscall::Sendmmsg => Some(349),
+        Syscall::Sendmsg => Some(341),
+        Syscall::Sendto => Some(335),
+        Syscall::SetRobustList => Some(300),
+        Syscall::SetTidAddress => Some(232),
+        Syscall::Setsockopt => Some(339),
+        Syscall::Sigaltstack => Some(185),
+        Syscall::Socket => Some(326),
+        Syscall::Socketcall => Some(102),
+        Syscall::Stat => Some(106),
+        Syscall::Statfs => Some(99),
+        Syscall::Statx => Some(383),
+        Syscall::Tgkill => Some(250),
+        Syscall::Tkill => Some(208),
+        Syscall::Truncate => Some(92),
+        Syscall::Unlink => Some(10),
+        Syscall::Unlinkat => Some(292),
+        Syscall::Vfork => Some(189),
+        Syscall::Wait4 => Some(114),
+        Syscall::Waitid => Some(272),
+        Syscall::Write => Some(4),
+        Syscall::Writev => Some(146),
+        Syscall::ArchPrctl | Syscall::SetThreadArea => None,
+        Syscall::ClockGetresTime64 | Syscall::ClockGettime64 | Syscall::ClockNanosleepTime64 |
+        Syscall::FutexTime64 | Syscall::PpollTime64 => None,
+    }
+}
+
+/// aarch64 uses the generic table, but kept `renameat`.
+fn aarch64_number(syscall: Syscall) -> Option<u32> {
+    match syscall {
+        Syscall::Renameat => Some(38),
+        _ => generic_number(syscall),
+    }
+}
+
+/// The numbers from the kernel's generic `unistd.h`, which newer architectures share.
+fn generic_number(syscall: Syscall) -> Option<u32> {
+    match syscall {
+        Syscall::Accept => Some(202),
+        Syscall::Accept4 => Some(242),
+        Syscall::Bind => Some(200),
+        Syscall::Brk => Some(214),
+        Syscall::ClockGetres => Some(114),
+        Syscall::ClockGettime => Some(113),
+        Syscall::ClockNanosleep => Some(115),
+        Syscall::Clone => Some(220),
+        Syscall::Clone3 => Some(435),
+        Syscall::Close => Some(57),
+        Syscall::Connect => Some(203),
+        Syscall::Dup3 => Some(24),
+        Syscall::Execve => Some(221),
+        Syscall::Exit => Some(93),
+        Syscall::ExitGroup => Some(94),
+        Syscall::Faccessat => Some(48),
+        Syscall::Faccessat2 => Some(439),
+        Syscall::Fcntl => Some(25),
+        Syscall::Fdatasync => Some(83),
+        Syscall::Fstat => Some(80),
+        Syscall::Fstatfs => Some(44),
+        Syscall::Fsync => Some(82),
+        Syscall::Ftruncate => Some(46),
+        Syscall::Futex => Some(98),
+        Syscall::Getdents64 => Some(61),
+        Syscall::Getpid => Some(172),
+        Syscall::Getrandom => Some(278),
+        Syscall::Getsockname => Some(204),
+        Syscall::Getsockopt => Some(209),
+        Syscall::Gettid => Some(178),
+        Syscall::Getuid => Some(174),
+        Syscall::Ioctl => Some(29),
+        Syscall::Kill => Some(129),
+        Syscall::Listen => Some(201),
+        Syscall::Lseek => Some(62),
+        Syscall::Madvise => Some(233),
+        Syscall::Mkdirat => Some(34),
+        Syscall::Mmap => Some(222),
+        Syscall::Mprotect => Some(226),
+        Syscall::Mremap => Some(216),
+        Syscall::Munmap => Some(215),
+        Syscall::Nanosleep => Some(101),
+        Syscall::Newfstatat => Some(79),
+        Syscall::Openat => Some(56),
+        Syscall::Pipe2 => Some(59),
+        Syscall::Ppoll => Some(73),
+        Syscall::Pread64 => Some(67),
+        Syscall::Prlimit64 => Some(261),
+        Syscall::Pwrite64 => Some(68),
+        Syscall::Read => Some(63),
+        Syscall::Readlinkat => Some(78),
+        Syscall::Readv => Some(65),
+        Syscall::Recvfrom => Some(207),
+        Syscall::Recvmsg => Some(212),
+        Syscall::Renameat2 => Some(276),
+        Syscall::Rseq => Some(293),
+        Syscall::RtSigaction => Some(134),
+        Syscall::RtSigprocmask => Some(135),
+        Syscall::RtSigreturn => Some(139),
+        Syscall::SchedGetaffinity => Some(123),
+        Syscall::SchedYield => Some(124),
+        Syscall::Sendmmsg => Some(269),
+        Syscall::Sendmsg => Some(211),
+        Syscall::Sendto => Some(206),
+        Syscall::SetRobustList => Some(99),
+        Syscall::SetTidAddress => Some(96),
+        Syscall::Setsockopt => Some(208),
+        Syscall::Sigaltstack => Some(132),
+        Syscall::Socket => Some(198),
+        Syscall::Statfs => Some(43),
+        Syscall::Statx => Some(291),
+        Syscall::Tgkill => Some(131),
+        Syscall::Tkill => Some(130),
+        Syscall::Truncate => Some(45),
+        Syscall::Unlinkat => Some(35),
+        Syscall::Wait4 => Some(260),
+        Syscall::Waitid => Some(95),
+        Syscall::Write => Some(64),
+        Syscall::Writev => Some(66),
+        Syscall::Access | Syscall::ArchPrctl | Syscall::Dup2 | Syscall::Fork | Syscall::Lstat |
+        Syscall::Mkdir | Syscall::Open | Syscall::Poll | Syscall::Readlink | Syscall::Rename |
+        Syscall::Renameat | Syscall::Rmdir | Syscall::Stat | Syscall::Unlink |
+        Syscall::Vfork => None,
+        Syscall::ClockGetresTime64 | Syscall::ClockGettime64 | Syscall::ClockNanosleepTime64 |
+        Syscall::FutexTime64 | Syscall::Ipc | Syscall::PpollTime64 | Syscall::SetThreadArea |
+        Syscall::Socketcall => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Architecture, Syscall, AARCH64, ARM, POWERPC, POWERPC64, POWERPC64LE, RISCV64};
+    use super::{X86, X86_64};
+    use super::Syscall::*;
+    use libc;
+
+    static ALL_SYSCALLS: [Syscall; 102] = [
+        Syscall::Accept,
+        Syscall::Accept4,
+        Syscall::Access,
+        Syscall::ArchPrctl,
+        Syscall::Bind,
+        Syscall::Brk,
+        Syscall::ClockGetres,
+        Syscall::ClockGetresTime64,
+        Syscall::ClockGettime,
+        Syscall::ClockGettime64,
+        Syscall::ClockNanosleep,
+        Syscall::ClockNanosleepTime64,
+        Syscall::Clone,
+        Syscall::Clone3,
+        Syscall::Close,
+        Syscall::Connect,
+        Syscall::Dup2,
+        Syscall::Dup3,
+        Syscall::Execve,
+        Syscall::Exit,
+        Syscall::ExitGroup,
+        Syscall::Faccessat,
+        Syscall::Faccessat2,
+        Syscall::Fcntl,
+        Syscall::Fdatasync,
+        Syscall::Fork,
+        Syscall::Fstat,
+        Syscall::Fstatfs,
+        Syscall::Fsync,
+        Syscall::Ftruncate,
+        Syscall::Futex,
+        Syscall::FutexTime64,
+        Syscall::Getdents64,
+        Syscall::Getpid,
+        Syscall::Getrandom,
+        Syscall::Getsockname,
+        Syscall::Getsockopt,
+        Syscall::Gettid,
+        Syscall::Getuid,
+        Syscall::Ioctl,
+        Syscall::Ipc,
+        Syscall::Kill,
+        Syscall::Listen,
+        Syscall::Lseek,
+        Syscall::Lstat,
+        Syscall::Madvise,
+        Syscall::Mkdir,
+        Syscall::Mkdirat,
+        Syscall::Mmap,
+        Syscall::Mprotect,
+        Syscall::Mremap,
+        Syscall::Munmap,
+        Syscall::Nanosleep,
+        Syscall::Newfstatat,
+        Syscall::Open,
+        Syscall::Openat,
+        Syscall::Pipe2,
+        Syscall::Poll,
+        Syscall::Ppoll,
+        Syscall::PpollTime64,
+        Syscall::Pread64,
+        Syscall::Prlimit64,
+        Syscall::Pwrite64,
+        Syscall::Read,
+        Syscall::Readlink,
+        Syscall::Readlinkat,
+        Syscall::Readv,
+        Syscall::Recvfrom,
+        Syscall::Recvmsg,
+        Syscall::Rename,
+        Syscall::Renameat,
+        Syscall::Renameat2,
+        Syscall::Rmdir,
+        Syscall::Rseq,
+        Syscall::RtSigaction,
+        Syscall::RtSigprocmask,
+        Syscall::RtSigreturn,
+        Syscall::SchedGetaffinity,
+        Syscall::SchedYield,
+        Syscall::Sendmmsg,
+        Syscall::Sendmsg,
+        Syscall::Sendto,
+        Syscall::SetRobustList,
+        Syscall::SetThreadArea,
+        Syscall::SetTidAddress,
+        Syscall::Setsockopt,
+        Syscall::Sigaltstack,
+        Syscall::Socket,
+        Syscall::Socketcall,
+        Syscall::Stat,
+        Syscall::Statfs,
+        Syscall::Statx,
+        Syscall::Tgkill,
+        Syscall::Tkill,
+        Syscall::Truncate,
+        Syscall::Unlink,
+        Syscall::Unlinkat,
+        Syscall::Vfork,
+        Syscall::Wait4,
+        Syscall::Waitid,
+        Syscall::Write,
+        Syscall::Writev,
+    ];
+
+    macro_rules! assert_numbers {
+        ($($syscall:ident => $name:ident,)*) => {
+            $(assert_eq!($syscall.number(), Some(libc::$name as u32), stringify!($syscall));)*
+        }
+    }
+
+    /// Every architecture that the filter supports.
+    static ARCHITECTURES: [&Architecture; 8] = [
+        &X86, &X86_64, &ARM, &AARCH64, &POWERPC, &POWERPC64, &POWERPC64LE, &RISCV64,
+    ];
+
+    /// Checks the table for the architecture under test against `libc`. Cross-compile the tests
+    /// to check the other tables.
+    #[cfg(not(any(target_arch="x86", target_arch="arm", target_arch="powerpc")))]
+    #[test]
+    fn host_numbers_match_libc() {
+        assert_numbers! {
+            Accept => SYS_accept,
+            Accept4 => SYS_accept4,
+            Bind => SYS_bind,
+            Brk => SYS_brk,
+            ClockGetres => SYS_clock_getres,
+            ClockGettime => SYS_clock_gettime,
+            ClockNanosleep => SYS_clock_nanosleep,
+            Clone => SYS_clone,
+            Clone3 => SYS_clone3,
+            Close => SYS_close,
+            Connect => SYS_connect,
+            Dup3 => SYS_dup3,
+            Execve => SYS_execve,
+            Exit => SYS_exit,
+            ExitGroup => SYS_exit_group,
+            Faccessat => SYS_faccessat,
+            Faccessat2 => SYS_faccessat2,
+            Fcntl => SYS_fcntl,
+            Fdatasync => SYS_fdatasync,
+            Fstat => SYS_fstat,
+            Fstatfs => SYS_fstatfs,
+            Fsync => SYS_fsync,
+            Ftruncate => SYS_ftruncate,
+            Futex => SYS_futex,
+            Getdents64 => SYS_getdents64,
+            Getpid => SYS_getpid,
+            Getrandom => SYS_getrandom,
+            Getsockname => SYS_getsockname,
+            Getsockopt => SYS_getsockopt,
+            Gettid => SYS_gettid,
+            Getuid => SYS_getuid,
+            Ioctl => SYS_ioctl,
+            Kill => SYS_kill,
+            Listen => SYS_listen,
+            Lseek => SYS_lseek,
+            Madvise => SYS_madvise,
+            Mkdirat => SYS_mkdirat,
+            Mmap => SYS_mmap,
+            Mprotect => SYS_mprotect,
+            Mremap => SYS_mremap,
+            Munmap => SYS_munmap,
+            Nanosleep => SYS_nanosleep,
+            Newfstatat => SYS_newfstatat,
+            Openat => SYS_openat,
+            Pipe2 => SYS_pipe2,
+            Ppoll => SYS_ppoll,
+            Pread64 => SYS_pread64,
+            Prlimit64 => SYS_prlimit64,
+            Pwrite64 => SYS_pwrite64,
+            Read => SYS_read,
+            Readlinkat => SYS_readlinkat,
+            Readv => SYS_readv,
+            Recvfrom => SYS_recvfrom,
+            Recvmsg => SYS_recvmsg,
+            Renameat2 => SYS_renameat2,
+            Rseq => SYS_rseq,
+            RtSigaction => SYS_rt_sigaction,
+            RtSigprocmask => SYS_rt_sigprocmask,
+            RtSigreturn => SYS_rt_sigreturn,
+            SchedGetaffinity => SYS_sched_getaffinity,
+            SchedYield => SYS_sched_yield,
+            Sendmmsg => SYS_sendmmsg,
+            Sendmsg => SYS_sendmsg,
+            Sendto => SYS_sendto,
+            SetRobustList => SYS_set_robust_list,
+            SetTidAddress => SYS_set_tid_address,
+            Setsockopt => SYS_setsockopt,
+            Sigaltstack => SYS_sigaltstack,
+            Socket => SYS_socket,
+            Statfs => SYS_statfs,
+            Statx => SYS_statx,
+            Tgkill => SYS_tgkill,
+            Tkill => SYS_tkill,
+            Truncate => SYS_truncate,
+            Unlinkat => SYS_unlinkat,
+            Wait4 => SYS_wait4,
+            Waitid => SYS_waitid,
+            Write => SYS_write,
+            Writev => SYS_writev,
+        }
+    }
+
+    #[cfg(target_arch="x86_64")]
+    #[test]
+    fn host_legacy_numbers_match_libc() {
+        assert_numbers! {
+            Access => SYS_access,
+            ArchPrctl => SYS_arch_prctl,
+            Dup2 => SYS_dup2,
+            Fork => SYS_fork,
+            Lstat => SYS_lstat,
+            Mkdir => SYS_mkdir,
+            Open => SYS_open,
+            Poll => SYS_poll,
+            Readlink => SYS_readlink,
+            Rename => SYS_rename,
+            Renameat => SYS_renameat,
+            Rmdir => SYS_rmdir,
+            Stat => SYS_stat,
+            Unlink => SYS_unlink,
+            Vfork => SYS_vfork,
+        }
+    }
+
+    #[cfg(any(target_arch="x86", target_arch="arm", target_arch="powerpc"))]
+    #[test]
+    fn host_32_bit_numbers_match_libc() {
+        assert_numbers! {
+            Accept4 => SYS_accept4,
+            ClockGettime => SYS_clock_gettime,
+            Fcntl => SYS_fcntl64,
+            Fstat => SYS_fstat64,
+            Fstatfs => SYS_fstatfs64,
+            Ftruncate => SYS_ftruncate64,
+            Futex => SYS_futex,
+            Lseek => SYS__llseek,
+            Lstat => SYS_lstat64,
+            Mmap => SYS_mmap2,
+            Newfstatat => SYS_fstatat64,
+            Openat => SYS_openat,
+            Read => SYS_read,
+            Socket => SYS_socket,
+            Stat => SYS_stat64,
+            Statfs => SYS_statfs64,
+            Truncate => SYS_truncate64,
+            Write => SYS_write,
+        }
+    }
+
+    #[cfg(target_arch="x86")]
+    #[test]
+    fn host_x86_numbers_match_libc() {
+        assert_numbers! {
+            Getuid => SYS_getuid32,
+            Ipc => SYS_ipc,
+            SetThreadArea => SYS_set_thread_area,
+            Socketcall => SYS_socketcall,
+        }
+    }
+
+    #[cfg(target_arch="arm")]
+    #[test]
+    fn host_arm_numbers_match_libc() {
+        assert_numbers! {
+            Accept => SYS_accept,
+            Getuid => SYS_getuid32,
+        }
+    }
+
+    #[cfg(any(target_arch="powerpc", target_arch="powerpc64"))]
+    #[test]
+    fn host_powerpc_numbers_match_libc() {
+        assert_numbers! {
+            Getuid => SYS_getuid,
+            Ipc => SYS_ipc,
+            Socketcall => SYS_socketcall,
+        }
+    }
+
+    #[cfg(any(target_arch="aarch64", target_arch="riscv64"))]
+    #[test]
+    fn host_has_no_legacy_syscalls() {
+        for syscall in [Access, Dup2, Fork, Open, Poll, Stat, Unlink, Vfork].iter() {
+            assert_eq!(syscall.number(), None, "{:?}", syscall);
+        }
+    }
+
+    fn assert_numbers_unique(architecture: &Architecture) {
+        let mut numbers: Vec<_> = ALL_SYSCALLS.iter().filter_map(|syscall| {
+            architecture.number(*syscall)
+        }).collect();
+        let count = numbers.len();
+        numbers.sort();
+        numbers.dedup();
+        assert_eq!(numbers.len(), count);
+    }
+
+    #[test]
+    fn numbers_are_unique() {
+        for architecture in ARCHITECTURES.iter() {
+            assert_numbers_unique(architecture);
+        }
+    }
+
+    #[test]
+    fn generic_architectures_leave_out_legacy_syscalls() {
+        for architecture in [&AARCH64, &RISCV64].iter() {
+            for syscall in [Access, ArchPrctl, Dup2, Fork, Open, Poll, Stat, Vfork].iter() {
+                assert_eq!(architecture.number(*syscall), None, "{:?}", syscall);
+            }
+            assert_eq!(architecture.number(Openat), Some(56));
+            assert_eq!(architecture.number(Newfstatat), Some(79));
+        }
+        assert_eq!(AARCH64.number(Renameat), Some(38));
+        assert_eq!(RISCV64.number(Renameat), None);
+    }
+
+    /// The system calls that only 32-bit architectures have.
+    static ONLY_32_BIT: [Syscall; 7] = [
+        Syscall::ClockGetresTime64,
+        Syscall::ClockGettime64,
+        Syscall::ClockNanosleepTime64,
+        Syscall::FutexTime64,
+        Syscall::Ipc,
+        Syscall::PpollTime64,
+        Syscall::Socketcall,
+    ];
+
+    #[test]
+    fn x86_64_has_every_64_bit_syscall() {
+        for syscall in ALL_SYSCALLS.iter() {
+            let expected = !ONLY_32_BIT.contains(syscall);
+            assert_eq!(X86_64.number(*syscall).is_some(), expected, "{:?}", syscall);
+        }
+    }
+
+    /// Checks that `architecture` numbers every system call except the `missing` ones.
+    fn assert_only_missing(architecture: &Architecture, missing: &[Syscall]) {
+        for syscall in ALL_SYSCALLS.iter() {
+            let expected = !missing.contains(syscall);
+            assert_eq!(architecture.number(*syscall).is_some(), expected, "{:?}", syscall);
+        }
+    }
+
+    #[test]
+    fn arm_has_no_multiplexers() {
+        assert_only_missing(&ARM, &[ArchPrctl, Ipc, SetThreadArea, Socketcall]);
+        assert_eq!(ARM.number(Mmap), Some(192));
+        assert_eq!(ARM.number(Openat), Some(322));
+    }
+
+    #[test]
+    fn powerpc_has_multiplexers() {
+        assert_only_missing(&POWERPC, &[ArchPrctl, SetThreadArea]);
+        for architecture in [&POWERPC64, &POWERPC64LE].iter() {
+            assert_only_missing(architecture, &[
+                ArchPrctl, ClockGetresTime64, ClockGettime64, ClockNanosleepTime64, FutexTime64,
+                PpollTime64, SetThreadArea,
+            ]);
+            assert_eq!(architecture.number(Socketcall), Some(102));
+            assert_eq!(architecture.number(Mmap), Some(90));
+        }
+        assert_eq!(POWERPC.number(Socketcall), Some(102));
+        assert_eq!(POWERPC.number(Mmap), Some(192));
+    }
+
+    #[test]
+    fn only_big_endian_powerpc_stores_high_halves_first() {
+        for architecture in ARCHITECTURES.iter() {
+            let big_endian = architecture.audit_arch == POWERPC.audit_arch ||
+                architecture.audit_arch == POWERPC64.audit_arch;
+            assert_eq!(architecture.is_little_endian(), !big_endian);
+        }
+    }
+
+    #[test]
+    fn x86_has_every_syscall_but_accept() {
+        for syscall in ALL_SYSCALLS.iter() {
+            let expected = *syscall != Accept;
+            assert_eq!(X86.number(*syscall).is_some(), expected, "{:?}", syscall);
+        }
+        assert_eq!(X86.number(Socketcall), Some(102));
+        assert_eq!(X86.number(Ipc), Some(117));
+        assert_eq!(X86.number(Mmap), Some(192));
+    }
+
+    #[test]
+    fn only_x86_64_has_another_abi() {
+        assert!(X86_64.abi_syscall_bit.is_some());
+        for architecture in ARCHITECTURES.iter().filter(|architecture| {
+            architecture.audit_arch != X86_64.audit_arch
+        }) {
+            assert_eq!(architecture.abi_syscall_bit, None);
+        }
+    }
+
+    #[test]
+    fn architectures_are_told_apart() {
+        for (index, architecture) in ARCHITECTURES.iter().enumerate() {
+            for other in ARCHITECTURES[index + 1..].iter() {
+                assert_ne!(architecture.audit_arch, other.audit_arch);
+            }
+        }
+    }
+}
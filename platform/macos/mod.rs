@@ -10,14 +10,23 @@
 
 //! Sandboxing on Mac OS X via Seatbelt (`sandboxd`).
 
+use error::{self, Error, Stage};
+use glob;
+use platform::unix::{environment, limits};
+use platform::unix::broker::BrokerClient;
 use platform::unix::process::Process;
 use profile::{self, AddressPattern, OperationSupport, OperationSupportLevel, PathPattern, Profile};
 use sandbox::{ChildSandboxMethods, Command, SandboxMethods};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use libc::{c_char, c_int};
 use std::ffi::{CStr, CString};
-use std::io::{self, Write};
-use std::path::Path;
+use std::fs;
+use std::io::Write;
+use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
 use std::ptr;
 use std::str;
 
@@ -31,19 +40,39 @@ impl OperationSupport for profile::Operation {
         match *self {
             profile::Operation::FileReadAll(_) |
             profile::Operation::FileReadMetadata(_) |
+            profile::Operation::FileWrite(_) |
+            profile::Operation::FileCreateAndUnlink(_) |
+            profile::Operation::Exec(_) |
+            profile::Operation::SignalSelf |
+            profile::Operation::SignalChildren |
             profile::Operation::NetworkOutbound(AddressPattern::All) |
             profile::Operation::NetworkOutbound(AddressPattern::Tcp(_)) |
             profile::Operation::NetworkOutbound(AddressPattern::LocalSocket(_)) |
+            profile::Operation::NetworkInbound(AddressPattern::All) |
+            profile::Operation::NetworkInbound(AddressPattern::Tcp(_)) |
+            profile::Operation::NetworkInbound(AddressPattern::LocalSocket(_)) |
             profile::Operation::SystemInfoRead |
+            profile::Operation::DeviceAccess(_) |
+            profile::Operation::ScratchDirectory { .. } |
             profile::Operation::PlatformSpecific(Operation::MachLookup(_)) => {
                 OperationSupportLevel::CanBeAllowed
             }
+            profile::Operation::NetworkOutbound(ref address_pattern) |
+            profile::Operation::NetworkInbound(ref address_pattern) => {
+                if ip_network_filter(address_pattern).is_some() {
+                    OperationSupportLevel::CanBeAllowed
+                } else {
+                    OperationSupportLevel::CannotBeAllowedPrecisely
+                }
+            }
         }
     }
 }
 
 /// Mac OS X-specific operations.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Operation {
     /// Lookups to the given Mach service are allowed.
     MachLookup(Vec<u8>),
@@ -66,25 +95,52 @@ impl SandboxMethods for Sandbox {
         &self.profile
     }
 
-    fn start(&self, command: &mut Command) -> io::Result<Process> {
-        command.env("GAOL_CHILD_PROCESS", "1").spawn()
+    fn start(&self, command: &mut Command) -> Result<Process,Error> {
+        // Create the scratch directories up front, so that the child finds them empty and we
+        // know to remove them once it exits.
+        let mut scratch_directories = vec![];
+        for operation in self.profile.allowed_operations().iter() {
+            if let profile::Operation::ScratchDirectory { ref path, .. } = *operation {
+                if let Err(error) = fs::create_dir(path) {
+                    remove_directories(&scratch_directories);
+                    return Err(error::io_error(Stage::CreateScratchDirectory, Some(path), error))
+                }
+                scratch_directories.push(path.clone())
+            }
+        }
+
+        match command.env("GAOL_CHILD_PROCESS", "1").spawn() {
+            Ok(mut process) => {
+                process.scratch_directories = scratch_directories;
+                Ok(process)
+            }
+            Err(error) => {
+                remove_directories(&scratch_directories);
+                Err(error::io_error(Stage::Spawn, None, error))
+            }
+        }
     }
 }
 
 pub struct ChildSandbox {
     profile: Profile,
+    broker: Option<BrokerClient>,
 }
 
 impl ChildSandbox {
     pub fn new(profile: Profile) -> ChildSandbox {
         ChildSandbox {
             profile: profile,
+            broker: BrokerClient::from_environment(),
         }
     }
 }
 
 impl ChildSandboxMethods for ChildSandbox {
-    fn activate(&self) -> Result<(),()> {
+    fn activate(&self) -> Result<(),Error> {
+        try!(limits::activate(self.profile.resource_limits()));
+        try!(environment::activate(self.profile.environment()));
+
         let mut sandbox_profile = Vec::new();
         sandbox_profile.write_all(SANDBOX_PROFILE_PROLOGUE).unwrap();
         for operation in self.profile.allowed_operations().iter() {
@@ -99,6 +155,29 @@ impl ChildSandboxMethods for ChildSandbox {
                     write_file_pattern(&mut sandbox_profile, file_pattern);
                     sandbox_profile.write_all(b")\n").unwrap();
                 }
+                profile::Operation::FileWrite(ref file_pattern) => {
+                    sandbox_profile.write_all(b"(allow file-write-data ").unwrap();
+                    write_file_pattern(&mut sandbox_profile, file_pattern);
+                    sandbox_profile.write_all(b")\n").unwrap();
+                }
+                profile::Operation::FileCreateAndUnlink(ref file_pattern) => {
+                    sandbox_profile.write_all(b"(allow file-write-create file-write-unlink ")
+                                   .unwrap();
+                    write_file_pattern(&mut sandbox_profile, file_pattern);
+                    sandbox_profile.write_all(b")\n").unwrap();
+                }
+                profile::Operation::Exec(ref file_pattern) => {
+                    sandbox_profile.write_all(b"(allow process-fork)\n").unwrap();
+                    sandbox_profile.write_all(b"(allow process-exec file-read* ").unwrap();
+                    write_file_pattern(&mut sandbox_profile, file_pattern);
+                    sandbox_profile.write_all(b")\n").unwrap();
+                }
+                profile::Operation::SignalSelf => {
+                    sandbox_profile.write_all(b"(allow signal (target self))\n").unwrap()
+                }
+                profile::Operation::SignalChildren => {
+                    sandbox_profile.write_all(b"(allow signal (target same-sandbox))\n").unwrap()
+                }
                 profile::Operation::NetworkOutbound(ref address_pattern) => {
                     sandbox_profile.write_all(b"(allow system-socket)\n").unwrap();
                     sandbox_profile.write_all(b"(allow network-outbound").unwrap();
@@ -107,6 +186,10 @@ impl ChildSandboxMethods for ChildSandbox {
                         AddressPattern::Tcp(port) => {
                             write!(&mut sandbox_profile, " (remote tcp \"*:{}\")", port).unwrap()
                         }
+                        AddressPattern::Ipv4Network(..) | AddressPattern::Ipv6Network(..) => {
+                            let filter = ip_network_filter(address_pattern).unwrap();
+                            write!(&mut sandbox_profile, " (remote {})", filter).unwrap()
+                        }
                         AddressPattern::LocalSocket(ref path) => {
                             sandbox_profile.write_all(b"( literal ").unwrap();
                             write_path(&mut sandbox_profile, path);
@@ -115,9 +198,40 @@ impl ChildSandboxMethods for ChildSandbox {
                     }
                     sandbox_profile.write_all(b")\n").unwrap();
                 }
+                profile::Operation::NetworkInbound(ref address_pattern) => {
+                    sandbox_profile.write_all(b"(allow system-socket)\n").unwrap();
+                    sandbox_profile.write_all(b"(allow network-bind network-inbound").unwrap();
+                    match *address_pattern {
+                        AddressPattern::All => {}
+                        AddressPattern::Tcp(port) => {
+                            write!(&mut sandbox_profile, " (local tcp \"*:{}\")", port).unwrap()
+                        }
+                        AddressPattern::Ipv4Network(..) | AddressPattern::Ipv6Network(..) => {
+                            let filter = ip_network_filter(address_pattern).unwrap();
+                            write!(&mut sandbox_profile, " (local {})", filter).unwrap()
+                        }
+                        AddressPattern::LocalSocket(ref path) => {
+                            sandbox_profile.write_all(b" (literal ").unwrap();
+                            write_path(&mut sandbox_profile, path);
+                            sandbox_profile.write_all(b")").unwrap();
+                        }
+                    }
+                    sandbox_profile.write_all(b")\n").unwrap();
+                }
                 profile::Operation::SystemInfoRead => {
                     sandbox_profile.write_all(b"(allow sysctl-read)\n").unwrap()
                 }
+                profile::Operation::ScratchDirectory { ref path, .. } => {
+                    sandbox_profile.write_all(b"(allow file-read* file-write* ").unwrap();
+                    write_file_pattern(&mut sandbox_profile, &PathPattern::Subpath(path.clone()));
+                    sandbox_profile.write_all(b")\n").unwrap();
+                }
+                profile::Operation::DeviceAccess(device) => {
+                    sandbox_profile.write_all(b"(allow file-read* file-write-data ").unwrap();
+                    write_file_pattern(&mut sandbox_profile,
+                                       &PathPattern::Literal(device.path().to_path_buf()));
+                    sandbox_profile.write_all(b")\n").unwrap();
+                }
                 profile::Operation::PlatformSpecific(Operation::MachLookup(ref service_name)) => {
                     sandbox_profile.write_all(b"(allow mach-lookup (global-name ").unwrap();
                     write_quoted_string(&mut sandbox_profile, service_name.as_slice());
@@ -134,12 +248,26 @@ impl ChildSandboxMethods for ChildSandbox {
             if sandbox_init(profile.as_ptr(), 0, &mut err) == 0 {
                 Ok(())
             } else {
-                error!("Failed to init sandbox: {:?}", CStr::from_ptr(err));
+                let message = CStr::from_ptr(err).to_string_lossy().into_owned();
+                error!("Failed to init sandbox: {:?}", message);
                 sandbox_free_error(err);
-                Err(())
+                Err(Error::Platform {
+                    stage: Stage::InitSeatbelt,
+                    message: message,
+                })
             }
         }
     }
+
+    fn broker(&self) -> Option<&BrokerClient> {
+        self.broker.as_ref()
+    }
+}
+
+fn remove_directories(directories: &[PathBuf]) {
+    for directory in directories.iter() {
+        drop(fs::remove_dir_all(directory))
+    }
 }
 
 fn write_file_pattern(sandbox_profile: &mut Vec<u8>, path_pattern: &PathPattern) {
@@ -152,10 +280,40 @@ fn write_file_pattern(sandbox_profile: &mut Vec<u8>, path_pattern: &PathPattern)
             sandbox_profile.write_all(b"(subpath ").unwrap();
             write_path(sandbox_profile, path)
         }
+        PathPattern::Glob(ref pattern) => {
+            // Leave out directories, as the Linux jail does, since it can't mount one without
+            // exposing its contents.
+            sandbox_profile.write_all(b"(require-all (require-not (vnode-type DIRECTORY)) (regex ")
+                           .unwrap();
+            write_quoted_string(sandbox_profile, glob::to_regex(pattern).as_bytes());
+            sandbox_profile.write_all(b")").unwrap()
+        }
     }
     sandbox_profile.write_all(b")").unwrap()
 }
 
+/// Returns the Seatbelt address filter, such as `ip4 "*:443"`, that matches exactly the addresses
+/// in an IP network pattern, or `None` if Seatbelt can't express the pattern. Seatbelt only
+/// understands any host or `localhost`, and either any port or a single one.
+fn ip_network_filter(address_pattern: &AddressPattern) -> Option<String> {
+    let (family, host, ports) = match *address_pattern {
+        AddressPattern::Ipv4Network(_, 0, ports) => ("ip4", "*", ports),
+        AddressPattern::Ipv4Network(network, 32, ports)
+                if network == Ipv4Addr::new(127, 0, 0, 1) => ("ip4", "localhost", ports),
+        AddressPattern::Ipv6Network(_, 0, ports) => ("ip6", "*", ports),
+        AddressPattern::Ipv6Network(network, 128, ports) if network.is_loopback() => {
+            ("ip6", "localhost", ports)
+        }
+        _ => return None,
+    };
+    let port = match ports {
+        None => "*".to_owned(),
+        Some(ports) if ports.first == ports.last => ports.first.to_string(),
+        Some(_) => return None,
+    };
+    Some(format!("{} \"{}:{}\"", family, host, port))
+}
+
 fn write_path(sandbox_profile: &mut Vec<u8>, path: &Path) {
     write_quoted_string(sandbox_profile, path.as_os_str().to_str().unwrap().as_bytes())
 }
@@ -0,0 +1,463 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A broker that opens files on behalf of a sandboxed process.
+//!
+//! Some files can only be chosen while the sandboxed process runs (for example, documents that the
+//! user picks), so they can't be named in the profile. Instead, the sandboxed process asks the
+//! process that started it to open them. A thread there checks each request against a
+//! `BrokerPolicy` and, if it passes, opens the file and sends the descriptor back with
+//! `SCM_RIGHTS`. Using a descriptor needs no further permission, so this works under every
+//! sandbox, however little of the filesystem it can see.
+//!
+//! Each request is a single datagram of a mode byte followed by the path. Each response is the
+//! `errno` value of the open (0 on success), accompanied by the descriptor if there is one.
+
+use sandbox::Command;
+
+use libc::{self, c_int, c_uint, c_void};
+use std::env;
+use std::ffi::{CString, OsStr};
+use std::fs::File;
+use std::io;
+use std::mem;
+use std::os::unix::prelude::{FromRawFd, OsStrExt};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// The environment variable that tells the sandboxed process which descriptor leads to its
+/// broker.
+const BROKER_FD_VARIABLE: &str = "GAOL_BROKER_FD";
+
+/// The environment variable that tells the sandboxed process that its broker may create files for
+/// it.
+const BROKER_CREATES_VARIABLE: &str = "GAOL_BROKER_CREATES";
+
+/// The longest path that may be requested, in bytes.
+const MAX_PATH_LENGTH: usize = 4096;
+
+/// Creates the channel's sockets close-on-exec, where `socketpair` can do that itself.
+#[cfg(any(target_os="android", target_os="linux", target_os="freebsd"))]
+const SOCKET_FLAGS: c_int = libc::SOCK_CLOEXEC;
+#[cfg(not(any(target_os="android", target_os="linux", target_os="freebsd")))]
+const SOCKET_FLAGS: c_int = 0;
+
+/// Marks received descriptors close-on-exec, where `recvmsg` can do that itself.
+#[cfg(any(target_os="android", target_os="linux", target_os="freebsd"))]
+const RECEIVE_FLAGS: c_int = libc::MSG_CMSG_CLOEXEC;
+#[cfg(not(any(target_os="android", target_os="linux", target_os="freebsd")))]
+const RECEIVE_FLAGS: c_int = 0;
+
+/// How a brokered file is to be opened.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OpenMode {
+    /// For reading, like `File::open`.
+    Read,
+    /// For writing, creating the file if it doesn't exist and truncating it if it does, like
+    /// `File::create`.
+    Create,
+}
+
+impl OpenMode {
+    fn from_byte(byte: u8) -> Option<OpenMode> {
+        match byte {
+            0 => Some(OpenMode::Read),
+            1 => Some(OpenMode::Create),
+            _ => None,
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            OpenMode::Read => 0,
+            OpenMode::Create => 1,
+        }
+    }
+
+    fn flags(self) -> c_int {
+        match self {
+            OpenMode::Read => libc::O_RDONLY,
+            OpenMode::Create => libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC,
+        }
+    }
+}
+
+/// Decides which files the broker opens for the sandboxed process.
+///
+/// The broker only asks about absolute paths without `..` components, and doesn't follow a
+/// symbolic link in the last component.
+pub trait BrokerPolicy: Send + 'static {
+    /// Returns true if the sandboxed process may open `path` in `mode`.
+    fn allows(&self, path: &Path, mode: OpenMode) -> bool;
+
+    /// Returns true if this policy may ever allow a file to be opened in `mode`. The broker
+    /// refuses requests in other modes without asking `allows`, and a sandboxed process whose
+    /// broker never creates files is kept from writing to files altogether. By default, every
+    /// mode may be allowed.
+    fn may_allow(&self, _: OpenMode) -> bool {
+        true
+    }
+}
+
+/// A policy that allows exactly the paths it has been given, which may change while the sandboxed
+/// process runs. Clones share the same list, so one can be kept to update the list after another
+/// has been handed to the broker.
+#[derive(Clone, Default)]
+pub struct Allowlist {
+    entries: Arc<Mutex<Vec<(PathBuf, OpenMode)>>>,
+    read_only: bool,
+}
+
+impl Allowlist {
+    /// Creates an empty allowlist.
+    pub fn new() -> Allowlist {
+        Allowlist::default()
+    }
+
+    /// Creates an empty allowlist that only ever allows files to be opened for reading, even if
+    /// paths are added to it in `OpenMode::Create`.
+    pub fn read_only() -> Allowlist {
+        Allowlist {
+            read_only: true,
+            ..Allowlist::default()
+        }
+    }
+
+    /// Allows `path` to be opened in `mode`.
+    pub fn allow<P>(&self, path: P, mode: OpenMode) where P: AsRef<Path> {
+        let entry = (path.as_ref().to_path_buf(), mode);
+        let mut entries = self.entries.lock().unwrap();
+        if !entries.contains(&entry) {
+            entries.push(entry)
+        }
+    }
+
+    /// Stops allowing `path` to be opened, in any mode. Descriptors that were already handed out
+    /// stay valid.
+    pub fn revoke<P>(&self, path: P) where P: AsRef<Path> {
+        self.entries.lock().unwrap().retain(|(allowed, _)| allowed != path.as_ref())
+    }
+}
+
+impl BrokerPolicy for Allowlist {
+    fn allows(&self, path: &Path, mode: OpenMode) -> bool {
+        self.entries.lock().unwrap().iter().any(|&(ref allowed, allowed_mode)| {
+            allowed == path && allowed_mode == mode
+        })
+    }
+
+    fn may_allow(&self, mode: OpenMode) -> bool {
+        !self.read_only || mode == OpenMode::Read
+    }
+}
+
+/// The pair of connected sockets between a broker and its sandboxed process.
+pub struct Channel {
+    broker_fd: c_int,
+    sandbox_fd: c_int,
+}
+
+impl Channel {
+    /// Creates the channel. Both ends are closed on `exec`, except in a process started with
+    /// the environment variable that names the channel; see `inherit`.
+    pub fn new() -> io::Result<Channel> {
+        let mut fds = [0, 0];
+        let result = unsafe {
+            libc::socketpair(libc::AF_UNIX, libc::SOCK_SEQPACKET | SOCKET_FLAGS, 0, &mut fds[0])
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error())
+        }
+        for &fd in fds.iter() {
+            unsafe {
+                libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+            }
+        }
+        Ok(Channel {
+            broker_fd: fds[0],
+            sandbox_fd: fds[1],
+        })
+    }
+
+    /// Returns the names and values of the environment variables that the sandboxed process needs
+    /// in order to find its end of the channel and to learn whether `policy` may create files.
+    pub fn environment<P>(&self, policy: &P) -> Vec<(&'static str, String)>
+                          where P: BrokerPolicy {
+        let mut variables = vec![(BROKER_FD_VARIABLE, self.sandbox_fd.to_string())];
+        if policy.may_allow(OpenMode::Create) {
+            variables.push((BROKER_CREATES_VARIABLE, "1".to_owned()))
+        }
+        variables
+    }
+
+    /// Closes the sandboxed process's end of the channel in this process and answers requests
+    /// from the other end with `policy` until the sandboxed process closes it.
+    pub fn serve<P>(mut self, policy: P) where P: BrokerPolicy {
+        let broker_fd = self.broker_fd;
+        unsafe {
+            libc::close(self.sandbox_fd);
+        }
+        self.broker_fd = -1;
+        self.sandbox_fd = -1;
+        thread::spawn(move || {
+            serve(broker_fd, &policy);
+            unsafe {
+                libc::close(broker_fd);
+            }
+        });
+    }
+}
+
+/// Lets the end of the channel named by `command`'s environment survive `exec`, if it names one.
+/// Call this in the sandboxed process, after forking.
+pub fn inherit(command: &Command) {
+    let name = CString::new(BROKER_FD_VARIABLE).unwrap();
+    let fd = command.env.get(&name).and_then(|fd| fd.to_str().ok()).and_then(|fd| fd.parse().ok());
+    if let Some(fd) = fd {
+        unsafe {
+            libc::fcntl(fd, libc::F_SETFD, 0);
+        }
+    }
+}
+
+impl Drop for Channel {
+    fn drop(&mut self) {
+        for &fd in [self.broker_fd, self.sandbox_fd].iter() {
+            if fd >= 0 {
+                unsafe {
+                    libc::close(fd);
+                }
+            }
+        }
+    }
+}
+
+fn serve<P>(socket: c_int, policy: &P) where P: BrokerPolicy {
+    let mut request = [0u8; MAX_PATH_LENGTH + 1];
+    loop {
+        let length = unsafe {
+            libc::recv(socket, request.as_mut_ptr() as *mut c_void, request.len(), 0)
+        };
+        if length < 0 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+            continue
+        }
+        if length <= 0 {
+            return
+        }
+
+        let result = match OpenMode::from_byte(request[0]) {
+            Some(mode) => open(Path::new(OsStr::from_bytes(&request[1..length as usize])),
+                               mode,
+                               policy),
+            None => Err(libc::EINVAL),
+        };
+        let sent = match result {
+            Ok(fd) => {
+                let sent = send_response(socket, 0, Some(fd));
+                unsafe {
+                    libc::close(fd);
+                }
+                sent
+            }
+            Err(errno) => send_response(socket, errno, None),
+        };
+        if !sent {
+            return
+        }
+    }
+}
+
+/// Opens `path` if the policy allows it, returning the descriptor or an `errno` value.
+fn open<P>(path: &Path, mode: OpenMode, policy: &P) -> Result<c_int,c_int> where P: BrokerPolicy {
+    if !path.is_absolute() || path.components().any(|component| component == Component::ParentDir) {
+        return Err(libc::EACCES)
+    }
+    if !policy.may_allow(mode) || !policy.allows(path, mode) {
+        return Err(libc::EACCES)
+    }
+    let c_path = match CString::new(path.as_os_str().as_bytes()) {
+        Ok(c_path) => c_path,
+        Err(_) => return Err(libc::EINVAL),
+    };
+    let fd = unsafe {
+        libc::open(c_path.as_ptr(),
+                   mode.flags() | libc::O_CLOEXEC | libc::O_NOCTTY | libc::O_NOFOLLOW,
+                   0o666)
+    };
+    if fd < 0 {
+        Err(io::Error::last_os_error().raw_os_error().unwrap_or(libc::EIO))
+    } else {
+        Ok(fd)
+    }
+}
+
+fn send_response(socket: c_int, errno: c_int, fd: Option<c_int>) -> bool {
+    let mut errno = errno;
+    let mut iov = libc::iovec {
+        iov_base: &mut errno as *mut c_int as *mut c_void,
+        iov_len: mem::size_of::<c_int>(),
+    };
+    let mut control = [0u64; 4];
+    unsafe {
+        let mut message: libc::msghdr = mem::zeroed();
+        message.msg_iov = &mut iov;
+        message.msg_iovlen = 1;
+        if let Some(fd) = fd {
+            message.msg_control = control.as_mut_ptr() as *mut c_void;
+            message.msg_controllen = libc::CMSG_SPACE(mem::size_of::<c_int>() as c_uint) as _;
+            let header = libc::CMSG_FIRSTHDR(&message);
+            (*header).cmsg_level = libc::SOL_SOCKET;
+            (*header).cmsg_type = libc::SCM_RIGHTS;
+            (*header).cmsg_len = libc::CMSG_LEN(mem::size_of::<c_int>() as c_uint) as _;
+            *(libc::CMSG_DATA(header) as *mut c_int) = fd;
+        }
+        libc::sendmsg(socket, &message, 0) == mem::size_of::<c_int>() as isize
+    }
+}
+
+/// The sandboxed process's connection to its broker. It stays open for the life of the process.
+pub struct BrokerClient {
+    socket: Mutex<c_int>,
+    creates: bool,
+}
+
+impl BrokerClient {
+    /// Finds the connection that the sandbox was started with, if any. This must be called before
+    /// the sandbox is activated, since that clears the environment.
+    pub fn from_environment() -> Option<BrokerClient> {
+        let socket: c_int = env::var(BROKER_FD_VARIABLE).ok().and_then(|fd| fd.parse().ok())?;
+        // Make sure the descriptor is still open, and keep it from leaking into programs that we
+        // run.
+        if unsafe { libc::fcntl(socket, libc::F_SETFD, libc::FD_CLOEXEC) } != 0 {
+            return None
+        }
+        Some(BrokerClient {
+            socket: Mutex::new(socket),
+            creates: env::var_os(BROKER_CREATES_VARIABLE).is_some(),
+        })
+    }
+
+    /// Returns true if the broker may open files for writing, which lets the sandboxed process
+    /// write to files.
+    pub fn may_create(&self) -> bool {
+        self.creates
+    }
+
+    /// Asks the broker to open `path` for reading.
+    pub fn open<P>(&self, path: P) -> io::Result<File> where P: AsRef<Path> {
+        self.open_with_mode(path.as_ref(), OpenMode::Read)
+    }
+
+    /// Asks the broker to open `path` for writing, creating or truncating it.
+    pub fn create<P>(&self, path: P) -> io::Result<File> where P: AsRef<Path> {
+        self.open_with_mode(path.as_ref(), OpenMode::Create)
+    }
+
+    /// Asks the broker to open `path` in `mode`. Paths the broker's policy doesn't allow fail
+    /// with `PermissionDenied`.
+    pub fn open_with_mode(&self, path: &Path, mode: OpenMode) -> io::Result<File> {
+        let path = path.as_os_str().as_bytes();
+        if path.len() > MAX_PATH_LENGTH {
+            return Err(io::Error::from_raw_os_error(libc::ENAMETOOLONG))
+        }
+        let mut request = Vec::with_capacity(path.len() + 1);
+        request.push(mode.to_byte());
+        request.extend_from_slice(path);
+
+        // Only one request may be outstanding at a time, or responses could go to the wrong
+        // thread.
+        let socket = self.socket.lock().unwrap();
+        let sent = unsafe {
+            libc::write(*socket, request.as_ptr() as *const c_void, request.len())
+        };
+        if sent != request.len() as isize {
+            return Err(io::Error::last_os_error())
+        }
+        self.receive_response(*socket)
+    }
+
+    fn receive_response(&self, socket: c_int) -> io::Result<File> {
+        let mut errno: c_int = 0;
+        let mut iov = libc::iovec {
+            iov_base: &mut errno as *mut c_int as *mut c_void,
+            iov_len: mem::size_of::<c_int>(),
+        };
+        let mut control = [0u64; 4];
+        unsafe {
+            let mut message: libc::msghdr = mem::zeroed();
+            message.msg_iov = &mut iov;
+            message.msg_iovlen = 1;
+            message.msg_control = control.as_mut_ptr() as *mut c_void;
+            message.msg_controllen = mem::size_of_val(&control) as _;
+            let received = libc::recvmsg(socket, &mut message, RECEIVE_FLAGS);
+            if received < 0 {
+                return Err(io::Error::last_os_error())
+            }
+            if received != mem::size_of::<c_int>() as isize {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                          "the broker closed the connection"))
+            }
+            if errno != 0 {
+                return Err(io::Error::from_raw_os_error(errno))
+            }
+            let header = libc::CMSG_FIRSTHDR(&message);
+            if header.is_null() || (*header).cmsg_level != libc::SOL_SOCKET ||
+                    (*header).cmsg_type != libc::SCM_RIGHTS {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          "the broker sent no file descriptor"))
+            }
+            let fd = *(libc::CMSG_DATA(header) as *const c_int);
+            if RECEIVE_FLAGS == 0 {
+                libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+            }
+            Ok(File::from_raw_fd(fd))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{inherit, Allowlist, BrokerPolicy, Channel, OpenMode};
+    use sandbox::Command;
+
+    use libc::{self, c_int};
+
+    fn is_closed_on_exec(fd: c_int) -> bool {
+        unsafe {
+            libc::fcntl(fd, libc::F_GETFD) & libc::FD_CLOEXEC != 0
+        }
+    }
+
+    #[test]
+    fn only_the_sandboxed_process_inherits_its_end() {
+        let channel = Channel::new().unwrap();
+        assert!(is_closed_on_exec(channel.broker_fd));
+        assert!(is_closed_on_exec(channel.sandbox_fd));
+
+        let mut command = Command::new("/bin/true");
+        for (name, value) in channel.environment(&Allowlist::new()).into_iter() {
+            command.env(name, value);
+        }
+        inherit(&command);
+        assert!(is_closed_on_exec(channel.broker_fd));
+        assert!(!is_closed_on_exec(channel.sandbox_fd));
+    }
+
+    #[test]
+    fn read_only_allowlists_never_create() {
+        let allowlist = Allowlist::read_only();
+        allowlist.allow("/tmp/output", OpenMode::Create);
+        assert!(!allowlist.may_allow(OpenMode::Create));
+        let channel = Channel::new().unwrap();
+        assert_eq!(channel.environment(&allowlist).len(), 1);
+        assert_eq!(channel.environment(&Allowlist::new()).len(), 2);
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reduction of the process environment to a profile's allowlist on POSIX systems.
+
+use error::{self, Error, Stage};
+use profile::EnvironmentVariable;
+
+use libc;
+use std::env;
+use std::ffi::{CString, OsString};
+use std::os::unix::ffi::OsStrExt;
+
+/// Removes every environment variable not in `allowlist` and sets those with fixed values.
+pub fn activate(allowlist: &[EnvironmentVariable]) -> Result<(),Error> {
+    // Read everything we're keeping before touching the environment.
+    let mut kept = vec![];
+    for variable in allowlist.iter() {
+        let value = match variable.value {
+            Some(ref value) => OsString::from(value.clone()),
+            None => {
+                match env::var_os(&variable.name) {
+                    Some(value) => value,
+                    None => continue,
+                }
+            }
+        };
+        kept.push((try!(cstring(variable.name.as_bytes())), try!(cstring(value.as_bytes()))))
+    }
+
+    for (name, _) in env::vars_os() {
+        let name = try!(cstring(name.as_bytes()));
+        if unsafe { libc::unsetenv(name.as_ptr()) } != 0 {
+            return Err(error::last_os_error(Stage::ClearEnvironment, None))
+        }
+    }
+    for (name, value) in kept.iter() {
+        if unsafe { libc::setenv(name.as_ptr(), value.as_ptr(), 1) } != 0 {
+            return Err(error::last_os_error(Stage::ClearEnvironment, None))
+        }
+    }
+    Ok(())
+}
+
+fn cstring(bytes: &[u8]) -> Result<CString,Error> {
+    CString::new(bytes).map_err(|_| {
+        Error::Os {
+            stage: Stage::ClearEnvironment,
+            path: None,
+            errno: libc::EINVAL,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::activate;
+    use error::{Error, Stage};
+    use profile::EnvironmentVariable;
+
+    use libc;
+
+    #[test]
+    fn errors_report_the_stage_and_errno() {
+        // The value is checked before anything is removed, so this leaves the environment alone.
+        assert_eq!(activate(&[EnvironmentVariable::fixed("GAOL_TEST", "a\0b")]),
+                   Err(Error::Os {
+                       stage: Stage::ClearEnvironment,
+                       path: None,
+                       errno: libc::EINVAL,
+                   }));
+    }
+}
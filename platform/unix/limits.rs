@@ -0,0 +1,74 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Resource limits on POSIX systems via `setrlimit`.
+
+use error::{self, Error, Stage};
+use profile::ResourceLimits;
+
+use libc::{self, rlim_t};
+
+/// Applies the given resource limits to the current process.
+pub fn activate(limits: &ResourceLimits) -> Result<(),Error> {
+    // The CPU time limit gets one second of slack in the hard limit: the kernel sends `SIGXCPU`
+    // when the soft limit is reached but `SIGKILL` when the hard limit is, and only the former
+    // lets us tell why the process died.
+    if let Some(cpu_seconds) = limits.cpu_seconds {
+        try!(set(libc::RLIMIT_CPU, cpu_seconds, cpu_seconds.saturating_add(1)))
+    }
+    for &(resource, limit) in [
+        (libc::RLIMIT_AS, limits.address_space_bytes),
+        (libc::RLIMIT_FSIZE, limits.file_size_bytes),
+        (libc::RLIMIT_NOFILE, limits.open_files),
+        (libc::RLIMIT_STACK, limits.stack_bytes),
+        (libc::RLIMIT_NPROC, limits.processes),
+    ].iter() {
+        if let Some(limit) = limit {
+            try!(set(resource, limit, limit))
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(target_os="linux", target_env="gnu"))]
+type Resource = libc::__rlimit_resource_t;
+#[cfg(not(all(target_os="linux", target_env="gnu")))]
+type Resource = libc::c_int;
+
+fn set(resource: Resource, soft_limit: u64, hard_limit: u64) -> Result<(),Error> {
+    let rlimit = libc::rlimit {
+        rlim_cur: soft_limit as rlim_t,
+        rlim_max: hard_limit as rlim_t,
+    };
+    if unsafe { libc::setrlimit(resource, &rlimit) } == 0 {
+        Ok(())
+    } else {
+        Err(error::last_os_error(Stage::SetResourceLimit, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::set;
+    use error::{Error, Stage};
+
+    use libc;
+
+    #[test]
+    fn errors_report_the_stage_and_errno() {
+        // A soft limit above the hard limit is refused without changing anything.
+        assert_eq!(set(libc::RLIMIT_NOFILE, 2, 1),
+                   Err(Error::Os {
+                       stage: Stage::SetResourceLimit,
+                       path: None,
+                       errno: libc::EINVAL,
+                   }));
+    }
+}
@@ -8,5 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+pub mod broker;
+pub mod environment;
+pub mod limits;
 pub mod process;
-
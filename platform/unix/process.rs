@@ -10,15 +10,24 @@
 
 //! Child process management on POSIX systems.
 
+use platform::unix::broker;
+use profile::ResourceLimit;
 use sandbox::Command;
 
-use libc::{execve, fork, pid_t, waitpid, WEXITSTATUS, WIFEXITED, WTERMSIG};
+use libc::{execve, fork, pid_t, rusage, wait4, SIGKILL, SIGXCPU, SIGXFSZ, WEXITSTATUS, WIFEXITED};
+use libc::WTERMSIG;
 use std::ffi::CString;
+use std::fs;
 use std::io;
+use std::mem;
+use std::path::PathBuf;
 use std::ptr;
 use std::str;
 
 pub fn exec(command: &Command) -> io::Error {
+    // Only the sandboxed process gets to keep its end of a broker channel.
+    broker::inherit(command);
+
     let mut args: Vec<_> = vec![command.module_path.as_ptr()];
     for arg in command.args.iter() {
         args.push(arg.as_ptr())
@@ -53,7 +62,13 @@ pub fn spawn(command: &Command) -> io::Result<Process> {
                 drop(exec(command));
                 panic!()
             }
-            pid => Ok(Process { pid: pid }),
+            pid => {
+                Ok(Process {
+                    pid,
+                    cpu_time_limit: None,
+                    scratch_directories: vec![],
+                })
+            }
         }
     }
 }
@@ -61,13 +76,23 @@ pub fn spawn(command: &Command) -> io::Result<Process> {
 #[allow(missing_copy_implementations)]
 pub struct Process {
     pub pid: pid_t,
+    /// The CPU time limit the process runs under, if it can only be enforced with `SIGKILL`.
+    ///
+    /// A process that is the init process of its own PID namespace (as the Linux sandbox is)
+    /// doesn't receive `SIGXCPU`, so the kernel kills it when it reaches the hard limit instead.
+    /// `wait` compares the CPU time of a killed process against this to tell the two apart.
+    pub cpu_time_limit: Option<u64>,
+    /// Directories created for the process's private use, which `wait` removes once it has
+    /// exited.
+    pub scratch_directories: Vec<PathBuf>,
 }
 
 impl Process {
     pub fn wait(&self) -> io::Result<ExitStatus> {
         let mut stat = 0;
+        let mut usage: rusage = unsafe { mem::zeroed() };
         loop {
-            let pid = unsafe { waitpid(-1, &mut stat, 0) };
+            let pid = unsafe { wait4(-1, &mut stat, 0, &mut usage) };
             if pid < 0 {
                 return Err(io::Error::last_os_error());
             }
@@ -76,12 +101,33 @@ impl Process {
             }
         }
 
+        for directory in self.scratch_directories.iter() {
+            drop(fs::remove_dir_all(directory))
+        }
+
         unsafe {
             if WIFEXITED(stat) {
                 Ok(ExitStatus::Code(WEXITSTATUS(stat) as i32))
             } else {
-                Ok(ExitStatus::Signal(WTERMSIG(stat) as i32))
+                match WTERMSIG(stat) {
+                    SIGXCPU => Ok(ExitStatus::LimitExceeded(ResourceLimit::CpuTime)),
+                    SIGXFSZ => Ok(ExitStatus::LimitExceeded(ResourceLimit::FileSize)),
+                    SIGKILL if self.exceeded_cpu_time_limit(&usage) => {
+                        Ok(ExitStatus::LimitExceeded(ResourceLimit::CpuTime))
+                    }
+                    signal => Ok(ExitStatus::Signal(signal)),
+                }
+            }
+        }
+    }
+
+    fn exceeded_cpu_time_limit(&self, usage: &rusage) -> bool {
+        match self.cpu_time_limit {
+            Some(limit) => {
+                let seconds = usage.ru_utime.tv_sec as u64 + usage.ru_stime.tv_sec as u64;
+                seconds >= limit
             }
+            None => false,
         }
     }
 }
@@ -89,6 +135,8 @@ impl Process {
 pub enum ExitStatus {
     Code(i32),
     Signal(i32),
+    /// The process was killed for exceeding one of the resource limits in its profile.
+    LimitExceeded(ResourceLimit),
 }
 
 impl ExitStatus {
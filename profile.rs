@@ -10,9 +10,15 @@
 
 //! Sandbox profiles—lists of permitted operations.
 
+use error::Error;
+use glob;
 use platform;
 
-use std::path::PathBuf;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::{Path, PathBuf};
 
 /// A sandbox profile, which specifies the set of operations that this process is allowed to
 /// perform. Operations not in the list are implicitly prohibited.
@@ -25,19 +31,22 @@ use std::path::PathBuf;
 /// All profiles implicitly prohibit *at least* the following operations. Future versions of `gaol`
 /// may add operations to selectively allow these.
 ///
-///    * Opening any file for writing.
+///    * Opening any file for writing, except as allowed by `FileWrite`.
+///
+///    * Creating or unlinking files, except as allowed by `FileCreateAndUnlink`.
 ///
-///    * Creating new processes.
+///    * Creating new processes, except to run programs allowed by `Exec`.
 ///
 ///    * Opening named pipes or System V IPC resources.
 ///
 ///    * Accessing System V semaphores.
 ///
-///    * Sending signals to other processes.
+///    * Sending signals to other processes, except as allowed by `SignalSelf` and
+///      `SignalChildren`.
 ///
 ///    * Tracing other processes.
 ///
-///    * Accepting inbound network connections.
+///    * Accepting inbound network connections, except as allowed by `NetworkInbound`.
 ///
 ///    * Any operation that requires superuser privileges on the current operating system.
 ///
@@ -75,41 +84,248 @@ use std::path::PathBuf;
 ///
 ///    * Exiting the process.
 ///
-/// Because of platform limitations, patterns within one profile are not permitted to overlap; the
-/// behavior is undefined if they do. For example, you may not allow metadata reads of the subpath
-/// rooted at `/dev` while allowing full reads of `/dev/null`; you must instead allow full reads of
-/// `/dev` or make the profile more restrictive.
-#[derive(Clone, Debug)]
+/// Because of platform limitations, the patterns of `FileReadAll` and `FileReadMetadata` operations
+/// within one profile are not permitted to overlap. For example, you may not allow metadata reads
+/// of the subpath rooted at `/dev` while allowing full reads of `/dev/null`; you must instead allow
+/// full reads of `/dev` or make the profile more restrictive. `Profile::new()` rejects such
+/// profiles, except that metadata reads of paths that are entirely covered by full reads are
+/// simply dropped, since full reads include metadata reads.
+///
+/// A profile may also carry resource limits, which cap the resources the sandboxed process may
+/// consume; see `ResourceLimits`. By default, no limits beyond those the platform imposes for its
+/// own purposes are applied.
+///
+/// When the sandbox is activated, the environment of the sandboxed process is cleared except for
+/// the variables in the profile's environment allowlist; see `EnvironmentVariable`. The allowlist
+/// is empty by default.
+///
+/// On Linux, a profile also says what happens when the sandboxed process makes a system call that
+/// it doesn't allow; see `ViolationPolicy`. By default, the process is killed.
+#[derive(Clone, Debug, PartialEq)]
 pub struct Profile {
     allowed_operations: Vec<Operation>,
+    resource_limits: ResourceLimits,
+    environment: Vec<EnvironmentVariable>,
+    violation_policy: ViolationPolicy,
 }
 
 /// An operation that this process is allowed to perform.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Operation {
     /// All file-related reading operations may be performed on this file.
     FileReadAll(PathPattern),
     /// Metadata (for example, `stat` or `readlink`) of this file may be read.
     FileReadMetadata(PathPattern),
+    /// Existing files may be opened for writing, written to, and truncated.
+    FileWrite(PathPattern),
+    /// Files and directories may be created, renamed, and unlinked. To write to the files once
+    /// they have been created, also allow `FileWrite` on the same pattern.
+    FileCreateAndUnlink(PathPattern),
+    /// Programs matching this pattern may be executed, and new processes may be created in order
+    /// to run them. The new program remains subject to this profile.
+    ///
+    /// Anything the program maps as executable, such as its dynamic linker and shared libraries,
+    /// must be allowed here too: on some platforms, other paths are not executable at all once
+    /// this operation is present.
+    Exec(PathPattern),
+    /// Signals may be sent to this process and its own threads.
+    SignalSelf,
+    /// Signals may be sent to processes created inside the same sandbox.
+    SignalChildren,
     /// Outbound network connections to the given address may be initiated.
     NetworkOutbound(AddressPattern),
+    /// Sockets may be bound to the given local address in order to listen for and accept
+    /// inbound network connections.
+    NetworkInbound(AddressPattern),
     /// System information may be read (via `sysctl` on Unix).
     SystemInfoRead,
+    /// This device may be opened for reading and writing. On Linux, it is made available inside
+    /// the sandbox's own filesystem at its usual path.
+    DeviceAccess(Device),
+    /// A private, empty directory appears at `path`, in which files and directories may be
+    /// freely created, written, and removed. It is discarded when the sandboxed process exits.
+    ///
+    /// On Linux, this is a `tmpfs` holding at most `max_bytes` bytes that only the sandboxed
+    /// process can see, and `path` needn't exist outside the sandbox. On macOS, `path` must not
+    /// exist yet; the directory is created there when the sandbox starts, and `max_bytes` is not
+    /// enforced.
+    ScratchDirectory {
+        /// Where the directory appears.
+        path: PathBuf,
+        /// The most the files in the directory may hold, in bytes.
+        max_bytes: u64,
+    },
     /// Platform-specific operations.
     PlatformSpecific(platform::Operation),
 }
 
+impl Operation {
+    /// Returns a coarser operation that allows everything this one does and more, if there is
+    /// one. For example, metadata reads widen to full reads of the same paths, and network access
+    /// to a specific address widens to network access to all addresses.
+    pub fn coarser(&self) -> Option<Operation> {
+        match *self {
+            Operation::FileReadMetadata(ref pattern) => {
+                Some(Operation::FileReadAll(pattern.clone()))
+            }
+            Operation::FileReadAll(ref pattern) => pattern.coarser().map(Operation::FileReadAll),
+            Operation::FileWrite(ref pattern) => pattern.coarser().map(Operation::FileWrite),
+            Operation::FileCreateAndUnlink(ref pattern) => {
+                pattern.coarser().map(Operation::FileCreateAndUnlink)
+            }
+            Operation::Exec(ref pattern) => pattern.coarser().map(Operation::Exec),
+            Operation::NetworkOutbound(AddressPattern::All) |
+            Operation::NetworkInbound(AddressPattern::All) => None,
+            Operation::NetworkOutbound(_) => Some(Operation::NetworkOutbound(AddressPattern::All)),
+            Operation::NetworkInbound(_) => Some(Operation::NetworkInbound(AddressPattern::All)),
+            Operation::SignalSelf |
+            Operation::SignalChildren |
+            Operation::SystemInfoRead |
+            Operation::DeviceAccess(_) |
+            Operation::ScratchDirectory { .. } |
+            Operation::PlatformSpecific(_) => None,
+        }
+    }
+}
+
 /// Describes a path or paths on the filesystem.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum PathPattern {
     /// One specific path.
     Literal(PathBuf),
     /// A directory and all of its contents, recursively.
     Subpath(PathBuf),
+    /// All paths matching an absolute glob pattern. `*` matches any run of characters and `?`
+    /// any single character, `[...]` matches one character from a set such as `[0-9]` (negated
+    /// with a leading `!`), and `\` escapes the next character. Wildcards never match `/`, but
+    /// unlike in the shell they do match a leading `.`, so `*` covers hidden files too.
+    ///
+    /// Only files match: a directory that matches the pattern is not allowed, and neither is
+    /// anything beneath it. Use `Subpath` for directories.
+    ///
+    /// On some platforms the pattern is expanded when the sandbox is activated, so files created
+    /// afterwards may not be covered even if they match.
+    Glob(String),
+}
+
+impl PathPattern {
+    /// Returns a subpath pattern describing the directory containing every path this pattern
+    /// describes, or `None` for subpaths, which can't usefully be widened.
+    pub fn coarser(&self) -> Option<PathPattern> {
+        match *self {
+            PathPattern::Literal(ref path) => {
+                path.parent().map(|parent| PathPattern::Subpath(parent.to_path_buf()))
+            }
+            PathPattern::Subpath(_) => None,
+            PathPattern::Glob(ref pattern) => {
+                Some(PathPattern::Subpath(glob::literal_prefix(pattern)))
+            }
+        }
+    }
+
+    /// Returns true if `path` is described by this pattern.
+    pub fn matches(&self, path: &Path) -> bool {
+        match *self {
+            PathPattern::Literal(ref literal) => path == literal,
+            PathPattern::Subpath(ref subpath) => path.starts_with(subpath),
+            PathPattern::Glob(ref pattern) => {
+                path.to_str().is_some_and(|path| glob::matches(pattern, path))
+            }
+        }
+    }
+
+    /// Returns true if some path may be described by both this pattern and `other`. When this
+    /// can't be determined exactly, as for two globs with wildcards in the same position, this
+    /// assumes that the patterns overlap.
+    pub fn overlaps(&self, other: &PathPattern) -> bool {
+        match (self, other) {
+            (PathPattern::Literal(a), PathPattern::Literal(b)) => a == b,
+            (PathPattern::Literal(literal), PathPattern::Subpath(subpath)) |
+            (PathPattern::Subpath(subpath), PathPattern::Literal(literal)) => {
+                literal.starts_with(subpath)
+            }
+            (PathPattern::Subpath(a), PathPattern::Subpath(b)) => {
+                a.starts_with(b) || b.starts_with(a)
+            }
+            (PathPattern::Glob(_), PathPattern::Literal(literal)) => self.matches(literal),
+            (PathPattern::Literal(literal), PathPattern::Glob(_)) => other.matches(literal),
+            (PathPattern::Glob(pattern), PathPattern::Subpath(subpath)) |
+            (PathPattern::Subpath(subpath), PathPattern::Glob(pattern)) => {
+                subpath.to_str().is_none_or(|subpath| glob::may_match_within(pattern, subpath))
+            }
+            (PathPattern::Glob(a), PathPattern::Glob(b)) => glob::may_overlap(a, b),
+        }
+    }
+
+    /// Returns true if every path described by `other` is also described by this pattern. When
+    /// this can't be determined exactly, as for two different globs, this returns false.
+    pub fn contains(&self, other: &PathPattern) -> bool {
+        match (self, other) {
+            (PathPattern::Literal(a), PathPattern::Literal(b)) => a == b,
+            (PathPattern::Subpath(subpath), PathPattern::Literal(path)) |
+            (PathPattern::Subpath(subpath), PathPattern::Subpath(path)) => {
+                path.starts_with(subpath)
+            }
+            (PathPattern::Subpath(subpath), PathPattern::Glob(pattern)) => {
+                subpath.to_str().is_some_and(|subpath| glob::is_within(pattern, subpath))
+            }
+            (PathPattern::Glob(_), PathPattern::Literal(literal)) => self.matches(literal),
+            (PathPattern::Glob(a), PathPattern::Glob(b)) => a == b,
+            (PathPattern::Literal(_), _) | (PathPattern::Glob(_), PathPattern::Subpath(_)) => {
+                false
+            }
+        }
+    }
+
+    /// Returns a pattern describing exactly the paths described by both this pattern and `other`.
+    ///
+    /// Returns `None` if no path is described by both, and also if the common paths can't be
+    /// described by a single pattern (as for two different globs), so the result never describes
+    /// more than either pattern does.
+    pub fn intersection(&self, other: &PathPattern) -> Option<PathPattern> {
+        match (self, other) {
+            (PathPattern::Literal(a), PathPattern::Literal(b)) if a == b => {
+                Some(self.clone())
+            }
+            (PathPattern::Literal(literal), PathPattern::Subpath(subpath)) |
+            (PathPattern::Subpath(subpath), PathPattern::Literal(literal))
+                    if literal.starts_with(subpath) => Some(PathPattern::Literal(literal.clone())),
+            (PathPattern::Subpath(a), PathPattern::Subpath(b)) => {
+                if a.starts_with(b) {
+                    Some(self.clone())
+                } else if b.starts_with(a) {
+                    Some(other.clone())
+                } else {
+                    None
+                }
+            }
+            (PathPattern::Glob(_), PathPattern::Literal(literal)) if self.matches(literal) => {
+                Some(other.clone())
+            }
+            (PathPattern::Literal(literal), PathPattern::Glob(_)) if other.matches(literal) => {
+                Some(self.clone())
+            }
+            (PathPattern::Glob(pattern), PathPattern::Subpath(subpath)) |
+            (PathPattern::Subpath(subpath), PathPattern::Glob(pattern))
+                    if subpath.to_str().is_some_and(|subpath| {
+                        glob::is_within(pattern, subpath)
+                    }) => {
+                Some(PathPattern::Glob(pattern.clone()))
+            }
+            (PathPattern::Glob(a), PathPattern::Glob(b)) if a == b => Some(self.clone()),
+            _ => None,
+        }
+    }
 }
 
 /// Describes a network address.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum AddressPattern {
     /// All network addresses.
     All,
@@ -117,6 +333,180 @@ pub enum AddressPattern {
     Tcp(u16),
     /// A local socket at the given path (for example, a Unix socket).
     LocalSocket(PathBuf),
+    /// Any IPv4 address within the network given by an address and a prefix length (for
+    /// example, `10.0.0.0` and `8` for `10.0.0.0/8`), optionally limited to a range of ports.
+    /// The prefix length may be at most 32.
+    #[cfg_attr(feature = "serde", serde(with = "::loader::ipv4_network"))]
+    Ipv4Network(Ipv4Addr, u8, Option<PortRange>),
+    /// Any IPv6 address within the network given by an address and a prefix length (for
+    /// example, `::1` and `128`), optionally limited to a range of ports. The prefix length may
+    /// be at most 128.
+    #[cfg_attr(feature = "serde", serde(with = "::loader::ipv6_network"))]
+    Ipv6Network(Ipv6Addr, u8, Option<PortRange>),
+}
+
+impl AddressPattern {
+    /// Returns true if the IP socket address `address` is described by this pattern.
+    pub fn matches(&self, address: &SocketAddr) -> bool {
+        match (self, address.ip()) {
+            (&AddressPattern::All, _) => true,
+            (&AddressPattern::Tcp(port), _) => address.port() == port,
+            (&AddressPattern::LocalSocket(_), _) => false,
+            (&AddressPattern::Ipv4Network(ref network, prefix_length, ref ports),
+             IpAddr::V4(ip)) => {
+                prefix_matches(&network.octets(), &ip.octets(), prefix_length) &&
+                    ports.is_none_or(|ports| ports.contains(address.port()))
+            }
+            (&AddressPattern::Ipv6Network(ref network, prefix_length, ref ports),
+             IpAddr::V6(ip)) => {
+                prefix_matches(&network.octets(), &ip.octets(), prefix_length) &&
+                    ports.is_none_or(|ports| ports.contains(address.port()))
+            }
+            (&AddressPattern::Ipv4Network(..), IpAddr::V6(_)) |
+            (&AddressPattern::Ipv6Network(..), IpAddr::V4(_)) => false,
+        }
+    }
+
+    /// Returns a pattern describing exactly the addresses described by both this pattern and
+    /// `other`.
+    ///
+    /// As with `PathPattern::intersection`, this returns `None` if the common addresses can't be
+    /// described by a single pattern (as for a TCP port and an IP network).
+    pub fn intersection(&self, other: &AddressPattern) -> Option<AddressPattern> {
+        match (self, other) {
+            (&AddressPattern::All, _) => Some(other.clone()),
+            (_, &AddressPattern::All) => Some(self.clone()),
+            (&AddressPattern::Ipv4Network(a, a_length, a_ports),
+             &AddressPattern::Ipv4Network(b, b_length, b_ports)) => {
+                let narrower = narrower_network((a, &a.octets(), a_length),
+                                                (b, &b.octets(), b_length));
+                match (narrower, port_range_intersection(a_ports, b_ports)) {
+                    (Some((network, prefix_length)), Some(ports)) => {
+                        Some(AddressPattern::Ipv4Network(network, prefix_length, ports))
+                    }
+                    _ => None,
+                }
+            }
+            (&AddressPattern::Ipv6Network(a, a_length, a_ports),
+             &AddressPattern::Ipv6Network(b, b_length, b_ports)) => {
+                let narrower = narrower_network((a, &a.octets(), a_length),
+                                                (b, &b.octets(), b_length));
+                match (narrower, port_range_intersection(a_ports, b_ports)) {
+                    (Some((network, prefix_length)), Some(ports)) => {
+                        Some(AddressPattern::Ipv6Network(network, prefix_length, ports))
+                    }
+                    _ => None,
+                }
+            }
+            _ if self == other => Some(self.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the narrower of two networks, each given as its address, the address's octets, and its
+/// prefix length, if it lies within the wider one.
+fn narrower_network<A>(a: (A, &[u8], u8), b: (A, &[u8], u8)) -> Option<(A, u8)> {
+    let ((a, a_octets, a_length), (b, b_octets, b_length)) = (a, b);
+    if a_length >= b_length && prefix_matches(b_octets, a_octets, b_length) {
+        Some((a, a_length))
+    } else if b_length > a_length && prefix_matches(a_octets, b_octets, a_length) {
+        Some((b, b_length))
+    } else {
+        None
+    }
+}
+
+/// Intersects two optional port ranges, where `None` stands for every port. Returns `None` if the
+/// ranges are disjoint.
+fn port_range_intersection(a: Option<PortRange>, b: Option<PortRange>)
+                           -> Option<Option<PortRange>> {
+    match (a, b) {
+        (None, ports) | (ports, None) => Some(ports),
+        (Some(a), Some(b)) => {
+            let range = PortRange {
+                first: if a.first > b.first { a.first } else { b.first },
+                last: if a.last < b.last { a.last } else { b.last },
+            };
+            if range.first <= range.last {
+                Some(Some(range))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Returns true if the first `prefix_length` bits of `network` and `address` are equal.
+fn prefix_matches(network: &[u8], address: &[u8], prefix_length: u8) -> bool {
+    let mut remaining = prefix_length as usize;
+    for (network_byte, address_byte) in network.iter().zip(address.iter()) {
+        if remaining == 0 {
+            break
+        }
+        let bits = if remaining < 8 { remaining } else { 8 };
+        let mask = 0xffu8 << (8 - bits);
+        if network_byte & mask != address_byte & mask {
+            return false
+        }
+        remaining -= bits
+    }
+    true
+}
+
+/// A character device that is safe to expose to sandboxed processes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Device {
+    /// `/dev/null`, which discards writes and reads as empty.
+    Null,
+    /// `/dev/zero`, which discards writes and reads as zero bytes.
+    Zero,
+    /// `/dev/full`, which fails every write with `ENOSPC` and reads as zero bytes.
+    Full,
+    /// `/dev/random`, which reads as random bytes.
+    Random,
+    /// `/dev/urandom`, which reads as random bytes without ever blocking.
+    Urandom,
+}
+
+impl Device {
+    /// Returns the path of the device node.
+    pub fn path(&self) -> &'static Path {
+        Path::new(match *self {
+            Device::Null => "/dev/null",
+            Device::Zero => "/dev/zero",
+            Device::Full => "/dev/full",
+            Device::Random => "/dev/random",
+            Device::Urandom => "/dev/urandom",
+        })
+    }
+}
+
+/// An inclusive range of TCP or UDP ports.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PortRange {
+    /// The first port in the range.
+    pub first: u16,
+    /// The last port in the range.
+    pub last: u16,
+}
+
+impl PortRange {
+    /// Creates a range containing only the given port.
+    pub fn single(port: u16) -> PortRange {
+        PortRange {
+            first: port,
+            last: port,
+        }
+    }
+
+    /// Returns true if `port` falls within this range.
+    pub fn contains(&self, port: u16) -> bool {
+        self.first <= port && port <= self.last
+    }
 }
 
 impl Profile {
@@ -126,27 +516,519 @@ impl Profile {
     /// can then inspect the operations via `OperationSupport::support()` to see which ones cannot
     /// be allowed and modify the set of allowed operations as necessary. We are deliberately
     /// strict here to reduce the probability of applications accidentally allowing operations due
-    /// to platform limitations.
-    pub fn new(allowed_operations: Vec<Operation>) -> Result<Profile,()> {
-        if allowed_operations.iter().all(|operation| {
-            match operation.support() {
-                OperationSupportLevel::NeverAllowed | OperationSupportLevel::CanBeAllowed => true,
-                OperationSupportLevel::CannotBeAllowedPrecisely |
-                OperationSupportLevel::AlwaysAllowed => false,
-            }
+    /// to platform limitations. Use `Profile::with_strictness()` to widen or drop such operations
+    /// instead.
+    ///
+    /// This also returns an error if the patterns of a `FileReadAll` and a `FileReadMetadata`
+    /// operation overlap, unless the metadata reads are redundant; see the documentation of
+    /// `Profile` for details, or if any operation is malformed.
+    pub fn new(allowed_operations: Vec<Operation>) -> Result<Profile,Error> {
+        if let Some(operation) = allowed_operations.iter().find(|operation| {
+            !is_well_formed(operation)
         }) {
-            Ok(Profile {
-                allowed_operations: allowed_operations,
+            return Err(Error::InvalidOperation(operation.clone()))
+        }
+        let allowed_operations = try!(resolve_overlaps(allowed_operations));
+        for operation in allowed_operations.iter() {
+            if !is_precisely_allowable(operation.support()) {
+                return Err(Error::UnsupportedOperation(operation.clone()))
+            }
+        }
+        Ok(Profile {
+            allowed_operations,
+            resource_limits: ResourceLimits::default(),
+            environment: vec![],
+            violation_policy: ViolationPolicy::default(),
+        })
+    }
+
+    /// Creates a new profile, treating operations that cannot be allowed precisely on this
+    /// platform according to `strictness` instead of necessarily rejecting them.
+    ///
+    /// Along with the profile, this returns a report of every operation that was widened or
+    /// dropped. Operations that are always allowed on this platform are dropped from the profile
+    /// under every strictness other than `Strict`, and reported as such.
+    pub fn with_strictness(allowed_operations: Vec<Operation>, strictness: Strictness)
+                           -> Result<(Profile, Vec<Downgrade>),Error> {
+        if strictness == Strictness::Strict {
+            return Profile::new(allowed_operations).map(|profile| (profile, vec![]))
+        }
+
+        let mut operations = vec![];
+        let mut downgrades = vec![];
+        for operation in allowed_operations.into_iter() {
+            let support = operation.support();
+            if is_precisely_allowable(support) {
+                operations.push(operation);
+                continue
+            }
+
+            let mut allowed = None;
+            if strictness == Strictness::WidenToCoarser &&
+                    support == OperationSupportLevel::CannotBeAllowedPrecisely {
+                let mut coarser = operation.coarser();
+                while let Some(candidate) = coarser {
+                    if is_precisely_allowable(candidate.support()) {
+                        allowed = Some(candidate);
+                        break
+                    }
+                    coarser = candidate.coarser()
+                }
+            }
+            if let Some(ref allowed) = allowed {
+                if !operations.contains(allowed) {
+                    operations.push(allowed.clone())
+                }
+            }
+            downgrades.push(Downgrade {
+                requested: operation,
+                allowed,
+                support,
             })
-        } else {
-            Err(())
         }
+        Profile::new(operations).map(|profile| (profile, downgrades))
     }
 
     /// Returns the list of allowed operations.
     pub fn allowed_operations(&self) -> &[Operation] {
         self.allowed_operations.as_slice()
     }
+
+    /// Returns the resource limits applied to the sandboxed process.
+    pub fn resource_limits(&self) -> &ResourceLimits {
+        &self.resource_limits
+    }
+
+    /// Sets the resource limits applied to the sandboxed process.
+    pub fn set_resource_limits(&mut self, resource_limits: ResourceLimits) {
+        self.resource_limits = resource_limits
+    }
+
+    /// Returns the environment variables that survive sandbox activation.
+    pub fn environment(&self) -> &[EnvironmentVariable] {
+        self.environment.as_slice()
+    }
+
+    /// Sets the environment variables that survive sandbox activation. If a name appears more
+    /// than once, the last entry for it wins.
+    pub fn set_environment(&mut self, environment: Vec<EnvironmentVariable>) {
+        let mut deduplicated: Vec<EnvironmentVariable> = vec![];
+        for variable in environment.into_iter() {
+            deduplicated.retain(|existing| existing.name != variable.name);
+            deduplicated.push(variable)
+        }
+        self.environment = deduplicated
+    }
+
+    /// Returns what happens when the sandboxed process makes a system call that this profile
+    /// doesn't allow.
+    pub fn violation_policy(&self) -> &ViolationPolicy {
+        &self.violation_policy
+    }
+
+    /// Sets what happens when the sandboxed process makes a system call that this profile
+    /// doesn't allow.
+    ///
+    /// This returns an error, leaving the profile unchanged, if any of the policy's actions is
+    /// invalid.
+    pub fn set_violation_policy(&mut self, violation_policy: ViolationPolicy)
+                                -> Result<(),Error> {
+        let actions = Some(&violation_policy.default_action).into_iter()
+            .chain(violation_policy.overrides.iter().map(|entry| &entry.action));
+        for action in actions {
+            if !action.is_valid() {
+                return Err(Error::InvalidViolationAction(*action))
+            }
+        }
+        self.violation_policy = violation_policy;
+        Ok(())
+    }
+
+    /// Adds the given operations to this profile, skipping any that it already allows.
+    ///
+    /// This returns an error, leaving the profile unchanged, under the same conditions as
+    /// `Profile::new()`: if any of the operations cannot be allowed precisely on this platform or
+    /// if a new operation's pattern overlaps with another one in a way this profile is not
+    /// permitted to contain (for example, full reads of `/dev/null` alongside metadata reads of
+    /// `/dev`).
+    pub fn extend(&mut self, operations: Vec<Operation>) -> Result<(),Error> {
+        let mut allowed_operations = self.allowed_operations.clone();
+        for operation in operations.into_iter() {
+            if !allowed_operations.contains(&operation) {
+                allowed_operations.push(operation)
+            }
+        }
+        self.allowed_operations = try!(Profile::new(allowed_operations)).allowed_operations;
+        Ok(())
+    }
+
+    /// Returns a profile that allows every operation allowed by either this profile or `other`.
+    /// Each resource limit is the looser of the two, and every environment variable kept by
+    /// either profile is kept. A fixed value takes precedence over an inherited one, and where
+    /// both profiles fix a variable, this profile's value wins. The violation policy is this
+    /// profile's.
+    ///
+    /// This fails under the same conditions as `extend()`.
+    pub fn merge(&self, other: &Profile) -> Result<Profile,Error> {
+        let mut profile = self.clone();
+        try!(profile.extend(other.allowed_operations.clone()));
+        profile.resource_limits = self.resource_limits.loosest(&other.resource_limits);
+        for variable in other.environment.iter() {
+            let kept = profile.environment.iter().any(|existing| {
+                existing.name == variable.name &&
+                    (existing.value.is_some() || variable.value.is_none())
+            });
+            if !kept {
+                profile.environment.retain(|existing| existing.name != variable.name);
+                profile.environment.push(variable.clone())
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Returns a profile that allows only the operations allowed by both this profile and
+    /// `other`. For example, full reads of the subpath `/usr` intersected with full reads of the
+    /// subpath `/usr/lib` allow full reads of `/usr/lib`.
+    ///
+    /// Where the operations common to both profiles can't be described exactly (as with two
+    /// different glob patterns), they are left out, so the result never allows more than either
+    /// profile does. Each resource limit is the tighter of the two. Only environment variables
+    /// kept by both profiles are kept; a fixed value takes precedence over an inherited one, and
+    /// a variable that the two profiles fix to different values is dropped. The violation policy
+    /// is this profile's.
+    pub fn intersect(&self, other: &Profile) -> Result<Profile,Error> {
+        let mut allowed_operations = vec![];
+        for a in self.allowed_operations.iter() {
+            for b in other.allowed_operations.iter() {
+                if let Some(operation) = intersect_operations(a, b) {
+                    if !allowed_operations.contains(&operation) {
+                        allowed_operations.push(operation)
+                    }
+                }
+            }
+        }
+        let mut profile = try!(Profile::new(allowed_operations));
+        profile.resource_limits = self.resource_limits.tightest(&other.resource_limits);
+        profile.violation_policy = self.violation_policy.clone();
+        for a in self.environment.iter() {
+            let b = match other.environment.iter().find(|b| b.name == a.name) {
+                Some(b) => b,
+                None => continue,
+            };
+            let value = match (&a.value, &b.value) {
+                (Some(a), Some(b)) if a != b => continue,
+                (&Some(ref value), _) | (_, &Some(ref value)) => Some(value.clone()),
+                (&None, &None) => None,
+            };
+            profile.environment.push(EnvironmentVariable {
+                name: a.name.clone(),
+                value,
+            })
+        }
+        Ok(profile)
+    }
+}
+
+/// Drops `FileReadMetadata` operations whose paths are all covered by `FileReadAll` operations,
+/// and returns an error if the patterns of any remaining pair of `FileReadAll` and
+/// `FileReadMetadata` operations overlap.
+fn resolve_overlaps(operations: Vec<Operation>) -> Result<Vec<Operation>,Error> {
+    let read_all_patterns: Vec<PathPattern> = operations.iter().filter_map(|operation| {
+        match *operation {
+            Operation::FileReadAll(ref pattern) => Some(pattern.clone()),
+            _ => None,
+        }
+    }).collect();
+
+    let mut resolved_operations = vec![];
+    for operation in operations.into_iter() {
+        if let Operation::FileReadMetadata(ref metadata_pattern) = operation {
+            if read_all_patterns.iter().any(|pattern| pattern.contains(metadata_pattern)) {
+                continue
+            }
+            if let Some(pattern) = read_all_patterns.iter().find(|pattern| {
+                pattern.overlaps(metadata_pattern)
+            }) {
+                return Err(Error::OverlappingPatterns(Operation::FileReadAll(pattern.clone()),
+                                                             operation.clone()))
+            }
+        }
+        resolved_operations.push(operation)
+    }
+    Ok(resolved_operations)
+}
+
+/// Returns an operation allowing exactly what both `a` and `b` allow, if there is one.
+fn intersect_operations(a: &Operation, b: &Operation) -> Option<Operation> {
+    match (a, b) {
+        (Operation::FileReadAll(a), Operation::FileReadAll(b)) => {
+            a.intersection(b).map(Operation::FileReadAll)
+        }
+        (&Operation::FileReadAll(ref all), &Operation::FileReadMetadata(ref metadata)) |
+        (&Operation::FileReadMetadata(ref metadata), &Operation::FileReadAll(ref all)) |
+        (&Operation::FileReadMetadata(ref all), &Operation::FileReadMetadata(ref metadata)) => {
+            all.intersection(metadata).map(Operation::FileReadMetadata)
+        }
+        (Operation::FileWrite(a), Operation::FileWrite(b)) => {
+            a.intersection(b).map(Operation::FileWrite)
+        }
+        (Operation::FileCreateAndUnlink(a), Operation::FileCreateAndUnlink(b)) => {
+            a.intersection(b).map(Operation::FileCreateAndUnlink)
+        }
+        (Operation::Exec(a), Operation::Exec(b)) => a.intersection(b).map(Operation::Exec),
+        (Operation::NetworkOutbound(a), Operation::NetworkOutbound(b)) => {
+            a.intersection(b).map(Operation::NetworkOutbound)
+        }
+        (Operation::NetworkInbound(a), Operation::NetworkInbound(b)) => {
+            a.intersection(b).map(Operation::NetworkInbound)
+        }
+        _ if a == b => Some(a.clone()),
+        _ => None,
+    }
+}
+
+/// Limits on the resources that a sandboxed process may consume, enforced by the kernel (via
+/// `setrlimit` on Unix). `None` leaves the corresponding limit as the process inherited it.
+///
+/// Limits are set in the child process when the sandbox is activated, and are inherited by any
+/// processes it creates.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct ResourceLimits {
+    /// CPU time, in seconds. A process that uses it up is terminated with `SIGXCPU`, which
+    /// `ExitStatus` reports as `LimitExceeded(ResourceLimit::CpuTime)`.
+    pub cpu_seconds: Option<u64>,
+    /// The size of the virtual address space, in bytes. Allocations beyond it fail.
+    pub address_space_bytes: Option<u64>,
+    /// The largest file that may be written, in bytes. A process that tries to write past it is
+    /// terminated with `SIGXFSZ`, which `ExitStatus` reports as
+    /// `LimitExceeded(ResourceLimit::FileSize)`.
+    pub file_size_bytes: Option<u64>,
+    /// One more than the highest file descriptor number that may be opened.
+    pub open_files: Option<u64>,
+    /// The size of the main thread's stack, in bytes.
+    pub stack_bytes: Option<u64>,
+    /// The number of processes (and, on Linux, threads) that may exist. Note that most kernels
+    /// count every process belonging to the same real user ID, not just those in the sandbox.
+    pub processes: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Returns true if no limits are set.
+    pub fn is_unlimited(&self) -> bool {
+        *self == ResourceLimits::default()
+    }
+
+    /// Returns limits that are each the looser of the corresponding limits in `self` and
+    /// `other`. A missing limit is looser than any value.
+    pub fn loosest(&self, other: &ResourceLimits) -> ResourceLimits {
+        fn loosest(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(if a > b { a } else { b }),
+                _ => None,
+            }
+        }
+        self.combine(other, loosest)
+    }
+
+    /// Returns limits that are each the tighter of the corresponding limits in `self` and
+    /// `other`.
+    pub fn tightest(&self, other: &ResourceLimits) -> ResourceLimits {
+        fn tightest(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(if a < b { a } else { b }),
+                (limit, None) | (None, limit) => limit,
+            }
+        }
+        self.combine(other, tightest)
+    }
+
+    fn combine<F>(&self, other: &ResourceLimits, f: F) -> ResourceLimits
+                  where F: Fn(Option<u64>, Option<u64>) -> Option<u64> {
+        ResourceLimits {
+            cpu_seconds: f(self.cpu_seconds, other.cpu_seconds),
+            address_space_bytes: f(self.address_space_bytes, other.address_space_bytes),
+            file_size_bytes: f(self.file_size_bytes, other.file_size_bytes),
+            open_files: f(self.open_files, other.open_files),
+            stack_bytes: f(self.stack_bytes, other.stack_bytes),
+            processes: f(self.processes, other.processes),
+        }
+    }
+}
+
+/// A resource limit whose violation terminated a process; see `ExitStatus::LimitExceeded`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ResourceLimit {
+    /// The CPU time limit, `ResourceLimits::cpu_seconds`.
+    CpuTime,
+    /// The file size limit, `ResourceLimits::file_size_bytes`. On Linux, this is also what stops
+    /// processes whose profile doesn't allow writing files from writing to files they already
+    /// have open.
+    FileSize,
+}
+
+/// An environment variable that is kept when the sandbox is activated. All others are removed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct EnvironmentVariable {
+    /// The name of the variable.
+    pub name: String,
+    /// The value to set the variable to, or `None` to keep the value it has when the sandbox is
+    /// activated (if any).
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub value: Option<String>,
+}
+
+impl EnvironmentVariable {
+    /// Keeps the variable `name` with whatever value it has at activation. Note that a sandboxed
+    /// process only has the variables its `Command` gave it; see `Command::inherit_env()`.
+    pub fn inherit<N>(name: N) -> EnvironmentVariable where N: Into<String> {
+        EnvironmentVariable {
+            name: name.into(),
+            value: None,
+        }
+    }
+
+    /// Sets the variable `name` to `value` at activation.
+    pub fn fixed<N, V>(name: N, value: V) -> EnvironmentVariable
+                       where N: Into<String>, V: Into<String> {
+        EnvironmentVariable {
+            name: name.into(),
+            value: Some(value.into()),
+        }
+    }
+}
+
+/// The largest `errno` value that a `ViolationAction::Errno` may carry.
+const MAX_ERRNO: u16 = 4095;
+
+/// What happens when the sandboxed process makes a system call that its profile doesn't allow.
+///
+/// Only Linux, whose `seccomp` filter rules on individual system calls, honors this. Elsewhere,
+/// forbidden operations fail the way the platform's sandbox makes them fail.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ViolationAction {
+    /// Kill the whole process.
+    #[default]
+    KillProcess,
+    /// Kill only the thread that made the call.
+    KillThread,
+    /// Fail the call with this `errno` value, which must be at most 4095.
+    Errno(u16),
+    /// Fail the call with `ENOSYS` after raising `SIGSYS` in the thread that made it. The handler
+    /// installed at activation records the number of the system call; see
+    /// `platform::linux::seccomp::last_trapped_syscall()`.
+    Trap,
+}
+
+impl ViolationAction {
+    /// Returns false if the kernel can't take this action: an `errno` value above 4095 would be
+    /// returned as a successful result instead.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            ViolationAction::Errno(errno) => errno <= MAX_ERRNO,
+            _ => true,
+        }
+    }
+}
+
+/// The actions taken when the sandboxed process makes system calls that its profile doesn't
+/// allow: a default, and overrides for particular system calls. Overrides never allow a call
+/// that the profile forbids; they only change what happens when it is made.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct ViolationPolicy {
+    /// The action for system calls that no override names.
+    pub default_action: ViolationAction,
+    /// The actions for particular system calls. If a system call is named more than once, the
+    /// first override wins.
+    pub overrides: Vec<ViolationOverride>,
+}
+
+impl ViolationPolicy {
+    /// Returns true if this is the default policy, which kills the process on any violation.
+    pub fn is_default(&self) -> bool {
+        *self == ViolationPolicy::default()
+    }
+
+    /// Returns a policy that takes `default_action` for every violation.
+    pub fn with_default_action(default_action: ViolationAction) -> ViolationPolicy {
+        ViolationPolicy {
+            default_action,
+            overrides: vec![],
+        }
+    }
+
+    /// Returns true if any violation raises `SIGSYS`.
+    pub fn traps(&self) -> bool {
+        self.default_action == ViolationAction::Trap ||
+            self.overrides.iter().any(|entry| entry.action == ViolationAction::Trap)
+    }
+}
+
+/// The action for one system call in a `ViolationPolicy`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct ViolationOverride {
+    /// The number of the system call on the architecture the sandbox runs on.
+    pub syscall: u32,
+    /// What happens when the system call is made but not allowed.
+    pub action: ViolationAction,
+}
+
+/// How `Profile::with_strictness()` treats operations that this platform can't allow precisely.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Strictness {
+    /// Reject the profile, like `Profile::new()` does.
+    Strict,
+    /// Replace each such operation with the least coarse operation that allows everything it
+    /// does and can be allowed precisely (see `Operation::coarser()`), or drop it if there is
+    /// none.
+    WidenToCoarser,
+    /// Drop such operations, so that the profile allows less than was asked for.
+    DropUnsupported,
+}
+
+/// An entry in the report returned by `Profile::with_strictness()`, describing an operation that
+/// this platform couldn't allow as requested.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Downgrade {
+    /// The operation as it was requested.
+    pub requested: Operation,
+    /// The coarser operation allowed in its place, or `None` if it was dropped.
+    pub allowed: Option<Operation>,
+    /// How well this platform supports the requested operation, which explains why it couldn't
+    /// be allowed as is.
+    pub support: OperationSupportLevel,
+}
+
+/// Returns false if `operation` can't mean anything, as for a network whose prefix length is
+/// longer than its addresses.
+fn is_well_formed(operation: &Operation) -> bool {
+    match *operation {
+        Operation::NetworkOutbound(ref pattern) | Operation::NetworkInbound(ref pattern) => {
+            match *pattern {
+                AddressPattern::Ipv4Network(_, prefix_length, _) => prefix_length <= 32,
+                AddressPattern::Ipv6Network(_, prefix_length, _) => prefix_length <= 128,
+                _ => true,
+            }
+        }
+        _ => true,
+    }
+}
+
+fn is_precisely_allowable(support: OperationSupportLevel) -> bool {
+    match support {
+        OperationSupportLevel::NeverAllowed | OperationSupportLevel::CanBeAllowed => true,
+        OperationSupportLevel::CannotBeAllowedPrecisely |
+        OperationSupportLevel::AlwaysAllowed => false,
+    }
 }
 
 /// How precisely an operation can be allowed on this platform.
@@ -173,3 +1055,182 @@ pub trait OperationSupport {
     fn support(&self) -> OperationSupportLevel;
 }
 
+#[cfg(test)]
+mod tests {
+    use super::{resolve_overlaps, AddressPattern, Operation, PathPattern, Error, Profile};
+    use super::{ViolationAction, ViolationOverride, ViolationPolicy};
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+    use std::path::PathBuf;
+
+    fn literal(path: &str) -> PathPattern {
+        PathPattern::Literal(PathBuf::from(path))
+    }
+
+    fn subpath(path: &str) -> PathPattern {
+        PathPattern::Subpath(PathBuf::from(path))
+    }
+
+    fn glob(pattern: &str) -> PathPattern {
+        PathPattern::Glob(pattern.to_owned())
+    }
+
+    /// Checks that full reads of `all` alongside metadata reads of `metadata` are kept as is.
+    fn assert_kept(all: PathPattern, metadata: PathPattern) {
+        let operations = vec![Operation::FileReadAll(all), Operation::FileReadMetadata(metadata)];
+        assert_eq!(resolve_overlaps(operations.clone()), Ok(operations));
+    }
+
+    /// Checks that the metadata reads are dropped because the full reads cover them.
+    fn assert_dropped(all: PathPattern, metadata: PathPattern) {
+        let operations = vec![Operation::FileReadMetadata(metadata), Operation::FileReadAll(all)];
+        assert_eq!(resolve_overlaps(operations.clone()), Ok(vec![operations[1].clone()]));
+    }
+
+    /// Checks that the combination is rejected.
+    fn assert_rejected(all: PathPattern, metadata: PathPattern) {
+        let (all, metadata) = (Operation::FileReadAll(all), Operation::FileReadMetadata(metadata));
+        assert_eq!(resolve_overlaps(vec![all.clone(), metadata.clone()]),
+                   Err(Error::OverlappingPatterns(all, metadata)));
+    }
+
+    #[test]
+    fn literal_and_literal() {
+        assert_kept(literal("/dev/null"), literal("/dev/zero"));
+        assert_dropped(literal("/dev/null"), literal("/dev/null"));
+    }
+
+    #[test]
+    fn literal_and_subpath() {
+        assert_kept(literal("/etc/passwd"), subpath("/dev"));
+        assert_rejected(literal("/dev/null"), subpath("/dev"));
+        assert_rejected(literal("/dev"), subpath("/dev"));
+    }
+
+    #[test]
+    fn literal_and_glob() {
+        assert_kept(literal("/dev/null"), glob("/dev/tty*"));
+        assert_rejected(literal("/dev/tty0"), glob("/dev/tty*"));
+    }
+
+    #[test]
+    fn subpath_and_literal() {
+        assert_kept(subpath("/usr"), literal("/etc/passwd"));
+        assert_kept(subpath("/dev/pts"), literal("/dev"));
+        assert_dropped(subpath("/dev"), literal("/dev/null"));
+    }
+
+    #[test]
+    fn subpath_and_subpath() {
+        assert_kept(subpath("/usr"), subpath("/usr2"));
+        assert_dropped(subpath("/usr"), subpath("/usr/lib"));
+        assert_rejected(subpath("/usr/lib"), subpath("/usr"));
+    }
+
+    #[test]
+    fn subpath_and_glob() {
+        assert_kept(subpath("/usr"), glob("/etc/*.conf"));
+        assert_dropped(subpath("/etc"), glob("/etc/*.conf"));
+        assert_rejected(subpath("/etc/ssl"), glob("/etc/*"));
+    }
+
+    #[test]
+    fn glob_and_literal() {
+        assert_kept(glob("/dev/tty*"), literal("/dev/null"));
+        assert_dropped(glob("/dev/tty*"), literal("/dev/tty0"));
+    }
+
+    #[test]
+    fn glob_and_subpath() {
+        assert_kept(glob("/etc/*.conf"), subpath("/usr"));
+        assert_rejected(glob("/etc/*.conf"), subpath("/etc"));
+    }
+
+    #[test]
+    fn glob_and_glob() {
+        assert_kept(glob("/etc/*.conf"), glob("/usr/*.conf"));
+        assert_kept(glob("/etc/*.conf"), glob("/etc/*/*.conf"));
+        assert_dropped(glob("/etc/*.conf"), glob("/etc/*.conf"));
+        assert_rejected(glob("/etc/*.conf"), glob("/etc/host*"));
+    }
+
+    #[test]
+    fn other_operations_are_unaffected() {
+        assert_eq!(resolve_overlaps(vec![
+            Operation::FileWrite(subpath("/tmp")),
+            Operation::FileReadMetadata(subpath("/tmp")),
+        ]), Ok(vec![
+            Operation::FileWrite(subpath("/tmp")),
+            Operation::FileReadMetadata(subpath("/tmp")),
+        ]));
+    }
+
+    fn ipv4(network: &str, prefix_length: u8, address: &str) -> bool {
+        let pattern = AddressPattern::Ipv4Network(network.parse().unwrap(), prefix_length, None);
+        let address: Ipv4Addr = address.parse().unwrap();
+        pattern.matches(&SocketAddr::from((address, 80)))
+    }
+
+    fn ipv6(network: &str, prefix_length: u8, address: &str) -> bool {
+        let pattern = AddressPattern::Ipv6Network(network.parse().unwrap(), prefix_length, None);
+        let address: Ipv6Addr = address.parse().unwrap();
+        pattern.matches(&SocketAddr::from((address, 80)))
+    }
+
+    #[test]
+    fn ipv4_prefixes() {
+        assert!(ipv4("10.0.0.0", 0, "192.168.1.1"));
+        assert!(ipv4("10.0.0.0", 7, "11.255.0.1"));
+        assert!(!ipv4("10.0.0.0", 7, "12.0.0.1"));
+        assert!(ipv4("10.0.0.0", 8, "10.255.255.255"));
+        assert!(!ipv4("10.0.0.0", 8, "11.0.0.0"));
+        assert!(ipv4("192.168.1.0", 24, "192.168.1.200"));
+        assert!(!ipv4("192.168.1.0", 24, "192.168.2.1"));
+        assert!(ipv4("127.0.0.1", 32, "127.0.0.1"));
+        assert!(!ipv4("127.0.0.1", 32, "127.0.0.2"));
+    }
+
+    #[test]
+    fn ipv6_prefixes() {
+        assert!(ipv6("fd00::", 0, "2001:db8::1"));
+        assert!(ipv6("fc00::", 7, "fdff::1"));
+        assert!(!ipv6("fc00::", 7, "fe00::1"));
+        assert!(ipv6("fd00::", 8, "fd12:3456::1"));
+        assert!(!ipv6("fd00::", 8, "fe00::1"));
+        assert!(ipv6("2001:db8::", 24, "2001:dff::1"));
+        assert!(!ipv6("2001:db8::", 24, "2001:e00::1"));
+        assert!(ipv6("::1", 128, "::1"));
+        assert!(!ipv6("::1", 128, "::2"));
+    }
+
+    #[test]
+    fn networks_with_long_prefixes_are_invalid() {
+        for pattern in [
+            AddressPattern::Ipv4Network(Ipv4Addr::new(10, 0, 0, 0), 33, None),
+            AddressPattern::Ipv6Network(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1), 129, None),
+        ].iter() {
+            let operation = Operation::NetworkOutbound(pattern.clone());
+            assert_eq!(Profile::new(vec![operation.clone()]).err(),
+                       Some(Error::InvalidOperation(operation)));
+        }
+    }
+
+    #[test]
+    fn errno_values_above_4095_are_invalid() {
+        let mut profile = Profile::new(vec![]).unwrap();
+        assert!(profile.set_violation_policy(
+            ViolationPolicy::with_default_action(ViolationAction::Errno(4095))).is_ok());
+        assert_eq!(profile.set_violation_policy(
+                       ViolationPolicy::with_default_action(ViolationAction::Errno(4096))),
+                   Err(Error::InvalidViolationAction(ViolationAction::Errno(4096))));
+        assert_eq!(profile.set_violation_policy(ViolationPolicy {
+                       default_action: ViolationAction::KillProcess,
+                       overrides: vec![ViolationOverride {
+                           syscall: 0,
+                           action: ViolationAction::Errno(u16::MAX),
+                       }],
+                   }),
+                   Err(Error::InvalidViolationAction(ViolationAction::Errno(u16::MAX))));
+        assert_eq!(*profile.violation_policy(),
+                   ViolationPolicy::with_default_action(ViolationAction::Errno(4095)));
+    }
+}
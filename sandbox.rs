@@ -10,6 +10,8 @@
 
 //! Creation and destruction of sandboxes.
 
+use error::{self, Error, Stage};
+use platform::broker;
 use platform::process::{self, Process};
 use profile::Profile;
 
@@ -20,6 +22,7 @@ use std::ffi::{CString, OsStr};
 use std::io;
 
 pub use platform::{ChildSandbox, Sandbox};
+pub use platform::broker::{Allowlist, BrokerClient, BrokerPolicy, OpenMode};
 
 /// All platform-specific sandboxes implement this trait.
 ///
@@ -30,14 +33,40 @@ pub trait SandboxMethods {
     fn profile(&self) -> &Profile;
 
     /// Spawns a child process eligible for sandboxing.
-    fn start(&self, command: &mut Command) -> io::Result<Process>;
+    fn start(&self, command: &mut Command) -> Result<Process,Error>;
+
+    /// Spawns a child process eligible for sandboxing, along with a broker that opens files on
+    /// its behalf whenever `policy` allows. The child reaches the broker through
+    /// `ChildSandboxMethods::broker`. Unless `policy` may allow files to be created, the broker
+    /// doesn't let the child write to files that the profile wouldn't.
+    fn start_with_broker<P>(&self, command: &mut Command, policy: P) -> Result<Process,Error>
+                            where P: BrokerPolicy {
+        let channel = try!(broker::Channel::new().map_err(|error| {
+            error::io_error(Stage::Spawn, None, error)
+        }));
+        let variables = channel.environment(&policy);
+        for &(name, ref value) in variables.iter() {
+            command.env(name, value);
+        }
+        let process = self.start(command);
+        for &(name, _) in variables.iter() {
+            command.env.remove(&cstring(name));
+        }
+        let process = try!(process);
+        channel.serve(policy);
+        Ok(process)
+    }
 }
 
 /// All platform-specific sandboxes in the child process implement this trait.
 pub trait ChildSandboxMethods {
     /// Activates the restrictions in this child process from here on out. Be sure to check the
-    /// return value!
-    fn activate(&self) -> Result<(),()>;
+    /// return value! On failure, the error says which step of the activation went wrong.
+    fn activate(&self) -> Result<(),Error>;
+
+    /// Returns the connection to the broker that this process was started with, if it was started
+    /// with `SandboxMethods::start_with_broker`.
+    fn broker(&self) -> Option<&BrokerClient>;
 }
 
 fn cstring<T>(path: T) -> CString
@@ -98,6 +127,22 @@ impl Command {
         self
     }
 
+    /// Passes the environment variable `key` through from the current process, if it is set.
+    pub fn inherit_env<T>(&mut self, key: T) -> &mut Command where T: AsRef<OsStr> {
+        if let Some(val) = env::var_os(key.as_ref()) {
+            self.env.insert(cstring(key), cstring(val));
+        }
+        self
+    }
+
+    /// Passes multiple environment variables through from the current process.
+    pub fn inherit_envs<'a,T>(&'a mut self, keys: &[T]) -> &'a mut Command where T: AsRef<OsStr> {
+        for key in keys.iter() {
+            self.inherit_env(key);
+        }
+        self
+    }
+
     /// Executes the command as a child process, which is returned.
     pub fn spawn(&self) -> io::Result<Process> {
         process::spawn(self)
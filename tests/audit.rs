@@ -0,0 +1,100 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+extern crate libc;
+
+#[cfg(target_os="linux")]
+use gaol::platform::linux::audit::{AuditReport, Violation};
+#[cfg(target_os="linux")]
+use gaol::platform::linux::supervisor;
+#[cfg(target_os="linux")]
+use gaol::profile::{Operation, PathPattern, Profile};
+#[cfg(target_os="linux")]
+use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox};
+#[cfg(target_os="linux")]
+use std::env;
+#[cfg(target_os="linux")]
+use std::fs::{self, File};
+#[cfg(target_os="linux")]
+use std::io::{Read, Write};
+#[cfg(target_os="linux")]
+use std::path::{Path, PathBuf};
+#[cfg(target_os="linux")]
+use std::process;
+
+#[cfg(target_os="linux")]
+fn profile(directory: &Path) -> Profile {
+    Profile::new(vec![
+        Operation::FileReadAll(PathPattern::Literal(directory.join("allowed"))),
+    ]).unwrap()
+}
+
+// Nothing is stopped in audit mode, so everything here should succeed.
+#[cfg(target_os="linux")]
+fn audit_test() {
+    let directory = PathBuf::from(env::var("GAOL_TEMP_DIRECTORY").unwrap());
+    ChildSandbox::audit(profile(&directory)).activate().unwrap();
+
+    let mut contents = String::new();
+    File::open(directory.join("allowed")).unwrap().read_to_string(&mut contents).unwrap();
+    File::open(directory.join("forbidden")).unwrap().read_to_string(&mut contents).unwrap();
+    File::create(directory.join("created")).unwrap().write_all(b"audited").unwrap();
+    unsafe {
+        assert!(libc::syscall(libc::SYS_getppid) > 0);
+    }
+    assert_eq!(contents, "allowedforbidden");
+}
+
+#[cfg(target_os="linux")]
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "audit_test" => return audit_test(),
+        _ => {}
+    }
+
+    // Audit mode depends on the supervisor, so only test it where the kernel supports that.
+    if !supervisor::is_supported() {
+        return
+    }
+
+    let directory = fs::canonicalize(env::temp_dir()).unwrap()
+                                                      .join(format!("gaoltest.audit.{}",
+                                                                    process::id()));
+    fs::create_dir(&directory).unwrap();
+    File::create(directory.join("allowed")).unwrap().write_all(b"allowed").unwrap();
+    File::create(directory.join("forbidden")).unwrap().write_all(b"forbidden").unwrap();
+
+    let report = AuditReport::new();
+    let mut command = Command::me().unwrap();
+    command.arg("audit_test").env("GAOL_TEMP_DIRECTORY", &directory);
+    let status = Sandbox::new(profile(&directory)).start_audited(&mut command, report.clone())
+                                                  .unwrap()
+                                                  .wait()
+                                                  .unwrap();
+    // Starting the process leaves the command's environment as it was.
+    assert_eq!(command.env.len(), 1);
+
+    let created = directory.join("created");
+    let violations = report.violations();
+    fs::remove_dir_all(&directory).unwrap();
+    assert!(status.success());
+
+    let allowed = Operation::FileReadAll(PathPattern::Literal(directory.join("allowed")));
+    let forbidden = Operation::FileReadAll(PathPattern::Literal(directory.join("forbidden")));
+    assert!(!violations.contains(&Violation::Operation(allowed)));
+    assert!(violations.contains(&Violation::Operation(forbidden)));
+    assert!(violations.contains(&Violation::Operation(Operation::FileWrite(
+        PathPattern::Literal(created.clone())))));
+    assert!(violations.contains(&Violation::Operation(Operation::FileCreateAndUnlink(
+        PathPattern::Literal(created)))));
+    assert!(violations.contains(&Violation::Syscall(libc::SYS_getppid as u32)));
+
+    // Each violation is only reported once.
+    for (index, violation) in violations.iter().enumerate() {
+        assert!(!violations[index + 1..].contains(violation));
+    }
+}
+
+#[cfg(not(target_os="linux"))]
+pub fn main() {}
@@ -0,0 +1,148 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+extern crate libc;
+
+use gaol::platform::process::ExitStatus;
+use gaol::profile::{Profile, ResourceLimit};
+use gaol::sandbox::{Allowlist, ChildSandbox, ChildSandboxMethods, Command, OpenMode, Sandbox};
+use gaol::sandbox::SandboxMethods;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Read, Write};
+use std::path::PathBuf;
+use std::os::unix::prelude::{AsRawFd, FromRawFd};
+use std::process;
+
+// The sandbox can't see the filesystem at all, so every file has to come from the broker.
+fn profile() -> Profile {
+    Profile::new(Vec::new()).unwrap()
+}
+
+fn allowance_test() {
+    let directory = PathBuf::from(env::var("GAOL_TEMP_DIRECTORY").unwrap());
+    let sandbox = ChildSandbox::new(profile());
+    sandbox.activate().unwrap();
+    let broker = sandbox.broker().unwrap();
+
+    let mut contents = [0; 7];
+    broker.open(directory.join("input")).unwrap().read_exact(&mut contents).unwrap();
+    assert_eq!(&contents, b"brokerd");
+    broker.create(directory.join("output")).unwrap().write_all(b"written").unwrap();
+
+    // Each path is only allowed in the mode it was allowed in.
+    let error = broker.create(directory.join("input")).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+    let error = broker.open(directory.join("output")).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+    let error = broker.open(directory.join("input/../input")).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+}
+
+// A broker that never creates files leaves the process unable to write to any, even one that it
+// inherited.
+fn read_only_test() {
+    let directory = PathBuf::from(env::var("GAOL_TEMP_DIRECTORY").unwrap());
+    let fd = env::var("GAOL_INHERITED_FD").unwrap().parse().unwrap();
+    let sandbox = ChildSandbox::new(profile());
+    sandbox.activate().unwrap();
+    let broker = sandbox.broker().unwrap();
+    assert!(!broker.may_create());
+    let error = broker.create(directory.join("output")).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+    check_inherited_write(unsafe { File::from_raw_fd(fd) }.write_all(b"written"))
+}
+
+/// On Linux, the file size limit is zero.
+#[cfg(target_os="linux")]
+fn check_inherited_write(result: io::Result<()>) {
+    assert_eq!(result.unwrap_err().raw_os_error(), Some(libc::EFBIG))
+}
+
+#[cfg(not(target_os="linux"))]
+fn check_inherited_write(result: io::Result<()>) {
+    result.unwrap()
+}
+
+fn prohibition_test() {
+    let directory = PathBuf::from(env::var("GAOL_TEMP_DIRECTORY").unwrap());
+    let sandbox = ChildSandbox::new(profile());
+    sandbox.activate().unwrap();
+    drop(sandbox.broker().unwrap().open(directory.join("forbidden")).unwrap())
+}
+
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "allowance_test" => return allowance_test(),
+        Some(ref arg) if arg == "read_only_test" => return read_only_test(),
+        Some(ref arg) if arg == "prohibition_test" => return prohibition_test(),
+        _ => {}
+    }
+
+    let directory = fs::canonicalize(env::temp_dir()).unwrap()
+                                                      .join(format!("gaoltest.broker.{}",
+                                                                    process::id()));
+    fs::create_dir(&directory).unwrap();
+    File::create(directory.join("input")).unwrap().write_all(b"brokerd").unwrap();
+    File::create(directory.join("forbidden")).unwrap();
+
+    let allowlist = Allowlist::new();
+    allowlist.allow(directory.join("input"), OpenMode::Read);
+    allowlist.allow(directory.join("output"), OpenMode::Create);
+    allowlist.allow(directory.join("forbidden"), OpenMode::Read);
+    // Revoking takes effect for requests that come after it.
+    allowlist.revoke(directory.join("forbidden"));
+
+    let allowance_status =
+        Sandbox::new(profile()).start_with_broker(Command::me().unwrap()
+                                                               .arg("allowance_test")
+                                                               .env("GAOL_TEMP_DIRECTORY",
+                                                                    &directory),
+                                                  allowlist.clone())
+                               .unwrap()
+                               .wait()
+                               .unwrap();
+
+    let read_only = Allowlist::read_only();
+    read_only.allow(directory.join("output"), OpenMode::Create);
+    let inherited = File::create(directory.join("inherited")).unwrap();
+    unsafe {
+        libc::fcntl(inherited.as_raw_fd(), libc::F_SETFD, 0);
+    }
+    let read_only_status =
+        Sandbox::new(profile()).start_with_broker(Command::me().unwrap()
+                                                               .arg("read_only_test")
+                                                               .env("GAOL_TEMP_DIRECTORY",
+                                                                    &directory)
+                                                               .env("GAOL_INHERITED_FD",
+                                                                    inherited.as_raw_fd()
+                                                                             .to_string()),
+                                                  read_only)
+                               .unwrap()
+                               .wait()
+                               .unwrap();
+    drop(inherited);
+
+    let prohibition_status =
+        Sandbox::new(profile()).start_with_broker(Command::me().unwrap()
+                                                               .arg("prohibition_test")
+                                                               .env("GAOL_TEMP_DIRECTORY",
+                                                                    &directory),
+                                                  allowlist.clone())
+                               .unwrap()
+                               .wait()
+                               .unwrap();
+
+    let mut output = String::new();
+    File::open(directory.join("output")).unwrap().read_to_string(&mut output).unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+    assert!(allowance_status.success());
+    // Unless the process ignores `SIGXFSZ`, exceeding the file size limit kills it.
+    match read_only_status {
+        ExitStatus::LimitExceeded(ResourceLimit::FileSize) => {}
+        status => assert!(status.success()),
+    }
+    assert!(!prohibition_status.success());
+    assert_eq!(output, "written");
+}
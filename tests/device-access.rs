@@ -0,0 +1,70 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+extern crate libc;
+
+use gaol::profile::{Device, Operation, Profile};
+use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::io::AsRawFd;
+
+fn allowance_profile() -> Profile {
+    Profile::new(vec![
+        Operation::DeviceAccess(Device::Null),
+        Operation::DeviceAccess(Device::Urandom),
+    ]).unwrap()
+}
+
+fn prohibition_profile() -> Profile {
+    Profile::new(vec![
+        Operation::DeviceAccess(Device::Null),
+    ]).unwrap()
+}
+
+fn allowance_test() {
+    ChildSandbox::new(allowance_profile()).activate().unwrap();
+    OpenOptions::new().write(true).open(Device::Null.path()).unwrap().write_all(b"gone\n").unwrap();
+    let mut bytes = [0; 16];
+    File::open(Device::Urandom.path()).unwrap().read_exact(&mut bytes).unwrap();
+
+    // `/dev/null` has no `ioctl`s of its own, so the driver refuses this one; what matters is that
+    // the sandbox lets it reach the driver.
+    let null = File::open(Device::Null.path()).unwrap();
+    let mut count: libc::c_int = 0;
+    unsafe {
+        libc::ioctl(null.as_raw_fd(), libc::FIONREAD, &mut count);
+    }
+    assert!(io::Error::last_os_error().raw_os_error() != Some(libc::EACCES));
+}
+
+fn prohibition_test() {
+    ChildSandbox::new(prohibition_profile()).activate().unwrap();
+    let mut bytes = [0; 16];
+    File::open(Device::Urandom.path()).unwrap().read_exact(&mut bytes).unwrap();
+}
+
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "allowance_test" => return allowance_test(),
+        Some(ref arg) if arg == "prohibition_test" => return prohibition_test(),
+        _ => {}
+    }
+
+    let allowance_status =
+        Sandbox::new(allowance_profile()).start(Command::me().unwrap().arg("allowance_test"))
+                                         .unwrap()
+                                         .wait()
+                                         .unwrap();
+    assert!(allowance_status.success());
+
+    let prohibition_status =
+        Sandbox::new(prohibition_profile()).start(Command::me().unwrap()
+                                                               .arg("prohibition_test"))
+                                           .unwrap()
+                                           .wait()
+                                           .unwrap();
+    assert!(!prohibition_status.success());
+}
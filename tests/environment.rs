@@ -0,0 +1,47 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+
+use gaol::profile::{EnvironmentVariable, Profile};
+use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
+use std::env;
+
+fn profile() -> Profile {
+    let mut profile = Profile::new(Vec::new()).unwrap();
+    profile.set_environment(vec![
+        EnvironmentVariable::inherit("GAOL_TEST_KEPT"),
+        EnvironmentVariable::inherit("GAOL_TEST_MISSING"),
+        EnvironmentVariable::fixed("GAOL_TEST_FIXED", "fixed"),
+    ]);
+    profile
+}
+
+pub fn allowlist_test() {
+    ChildSandbox::new(profile()).activate().unwrap();
+    assert_eq!(env::var("GAOL_TEST_KEPT").unwrap(), "kept");
+    assert_eq!(env::var("GAOL_TEST_FIXED").unwrap(), "fixed");
+    assert!(env::var_os("GAOL_TEST_MISSING").is_none());
+    assert!(env::var_os("GAOL_TEST_REMOVED").is_none());
+    assert!(env::var_os("GAOL_TEST_INHERITED").is_none());
+}
+
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "allowlist_test" => return allowlist_test(),
+        _ => {}
+    }
+
+    env::set_var("GAOL_TEST_INHERITED", "inherited");
+    let mut command = Command::me().unwrap();
+    command.arg("allowlist_test")
+           .env("GAOL_TEST_KEPT", "kept")
+           .env("GAOL_TEST_REMOVED", "removed")
+           .env("GAOL_TEST_FIXED", "overridden")
+           .inherit_env("GAOL_TEST_INHERITED")
+           .inherit_env("GAOL_TEST_UNSET");
+    assert!(command.env.len() == 4);
+
+    let status = Sandbox::new(profile()).start(&mut command).unwrap().wait().unwrap();
+    assert!(status.success());
+}
@@ -0,0 +1,76 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+
+use gaol::profile::{Operation, PathPattern, Profile};
+use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
+use std::env;
+use std::path::{Path, PathBuf};
+
+// Directories that the dynamic linker and system libraries live in.
+static LIBRARY_PATHS: [&str; 5] = [
+    "/lib",
+    "/lib64",
+    "/usr/lib",
+    "/usr/lib64",
+    "/System/Library",
+];
+
+fn allowance_profile(path: &Path) -> Profile {
+    let mut operations = vec![Operation::Exec(PathPattern::Literal(path.to_path_buf()))];
+    operations.extend(LIBRARY_PATHS.iter().map(|library_path| {
+        Operation::Exec(PathPattern::Subpath(PathBuf::from(library_path)))
+    }));
+    Profile::new(operations).unwrap()
+}
+
+fn prohibition_profile() -> Profile {
+    Profile::new(vec![
+        Operation::Exec(PathPattern::Subpath(PathBuf::from("/bogus")))
+    ]).unwrap()
+}
+
+fn run_exec_target(path: &PathBuf) {
+    let status = Command::new(path).arg("exec_target").spawn().unwrap().wait().unwrap();
+    assert!(status.success())
+}
+
+fn allowance_test() {
+    let path = env::current_exe().unwrap();
+    ChildSandbox::new(allowance_profile(&path)).activate().unwrap();
+    run_exec_target(&path)
+}
+
+fn prohibition_test() {
+    let path = env::current_exe().unwrap();
+    ChildSandbox::new(prohibition_profile()).activate().unwrap();
+    run_exec_target(&path)
+}
+
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "allowance_test" => return allowance_test(),
+        Some(ref arg) if arg == "prohibition_test" => return prohibition_test(),
+        Some(ref arg) if arg == "exec_target" => return,
+        _ => {}
+    }
+
+    let path = env::current_exe().unwrap();
+
+    let allowance_status =
+        Sandbox::new(allowance_profile(&path)).start(Command::me().unwrap()
+                                                                  .arg("allowance_test"))
+                                              .unwrap()
+                                              .wait()
+                                              .unwrap();
+    assert!(allowance_status.success());
+
+    let prohibition_status =
+        Sandbox::new(prohibition_profile()).start(Command::me().unwrap()
+                                                               .arg("prohibition_test"))
+                                           .unwrap()
+                                           .wait()
+                                           .unwrap();
+    assert!(!prohibition_status.success());
+}
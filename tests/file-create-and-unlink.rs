@@ -0,0 +1,133 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+extern crate libc;
+extern crate rand;
+
+use gaol::profile::{Operation, PathPattern, Profile};
+use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
+use libc::c_char;
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+use std::env;
+use std::ffi::{CString, OsStr};
+use std::fs::{self, File};
+use std::io::Write;
+use std::os::unix::prelude::OsStrExt;
+use std::path::{Path, PathBuf};
+
+// A conservative overapproximation of `PATH_MAX` on all platforms.
+const PATH_MAX: usize = 4096;
+
+fn allowance_profile(path: &Path) -> Profile {
+    Profile::new(vec![
+        Operation::FileCreateAndUnlink(PathPattern::Subpath(path.to_path_buf())),
+        Operation::FileWrite(PathPattern::Subpath(path.to_path_buf())),
+    ]).unwrap()
+}
+
+fn prohibition_profile() -> Profile {
+    Profile::new(vec![
+        Operation::FileCreateAndUnlink(PathPattern::Subpath(PathBuf::from("/bogus")))
+    ]).unwrap()
+}
+
+fn create_and_unlink(directory: &Path) {
+    let path = directory.join("created");
+    let mut file = File::create(&path).unwrap();
+    file.write_all(b"scratch\n").unwrap();
+    check_private(&file);
+    fs::remove_file(&path).unwrap()
+}
+
+/// The sandbox's `umask` keeps new files from other users.
+#[cfg(target_os="linux")]
+fn check_private(file: &File) {
+    use std::os::unix::fs::PermissionsExt;
+    assert_eq!(file.metadata().unwrap().permissions().mode() & 0o077, 0);
+}
+
+#[cfg(not(target_os="linux"))]
+fn check_private(_: &File) {}
+
+/// Linux can only make whole directories writable, so allowing specific names to be created would
+/// allow their siblings as well.
+#[cfg(target_os="linux")]
+fn check_specific_names_rejected(directory: &Path) {
+    use gaol::Error;
+    for pattern in [
+        PathPattern::Literal(directory.join("created")),
+        PathPattern::Glob(format!("{}/*.lock", directory.display())),
+    ].iter() {
+        let operation = Operation::FileCreateAndUnlink(pattern.clone());
+        assert_eq!(Profile::new(vec![operation.clone()]).err(),
+                   Some(Error::UnsupportedOperation(operation)));
+    }
+}
+
+#[cfg(not(target_os="linux"))]
+fn check_specific_names_rejected(_: &Path) {}
+
+fn allowance_test() {
+    let path = PathBuf::from(env::var("GAOL_TEMP_DIR").unwrap());
+    ChildSandbox::new(allowance_profile(&path)).activate().unwrap();
+    create_and_unlink(&path)
+}
+
+fn prohibition_test() {
+    let path = PathBuf::from(env::var("GAOL_TEMP_DIR").unwrap());
+    ChildSandbox::new(prohibition_profile()).activate().unwrap();
+    create_and_unlink(&path)
+}
+
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "allowance_test" => return allowance_test(),
+        Some(ref arg) if arg == "prohibition_test" => return prohibition_test(),
+        _ => {}
+    }
+
+    // Need to use `realpath` here for Mac OS X, because the temporary directory is usually a
+    // symlink.
+    let mut temp_path = env::temp_dir();
+    unsafe {
+        let c_temp_path =
+            CString::new(temp_path.as_os_str().to_str().unwrap().as_bytes()).unwrap();
+        let mut new_temp_path = [0u8; PATH_MAX];
+        realpath(c_temp_path.as_ptr(), new_temp_path.as_mut_ptr() as *mut c_char);
+        let pos = new_temp_path.iter().position(|&x| x == 0).unwrap();
+        temp_path = PathBuf::from(OsStr::from_bytes(&new_temp_path[..pos]));
+    }
+
+    let mut rng = rand::thread_rng();
+    let suffix: String = std::iter::repeat(())
+        .map(|()| rng.sample(Alphanumeric))
+        .take(6).collect();
+
+    temp_path.push(format!("gaoltest.{}", suffix));
+    fs::create_dir(&temp_path).unwrap();
+    check_specific_names_rejected(&temp_path);
+
+    let allowance_status = Sandbox::new(allowance_profile(
+            &temp_path)).start(Command::me().unwrap()
+                                            .arg("allowance_test")
+                                            .env("GAOL_TEMP_DIR", temp_path.clone())
+                                            .env("RUST_BACKTRACE", "1"))
+                        .unwrap()
+                        .wait()
+                        .unwrap();
+    assert!(allowance_status.success());
+
+    let prohibition_status = Sandbox::new(prohibition_profile()).start(
+        Command::me().unwrap().arg("prohibition_test").env("GAOL_TEMP_DIR", temp_path.clone())
+                                                      .env("RUST_BACKTRACE", "1"))
+                                                                .unwrap()
+                                                                .wait()
+                                                                .unwrap();
+    assert!(!prohibition_status.success());
+}
+
+extern "C" {
+    fn realpath(file_name: *const c_char, resolved_name: *mut c_char) -> *mut c_char;
+}
@@ -5,6 +5,7 @@ extern crate gaol;
 extern crate libc;
 extern crate rand;
 
+use gaol::Error;
 use gaol::profile::{Operation, PathPattern, Profile};
 use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
 use libc::c_char;
@@ -15,18 +16,18 @@ use std::ffi::{CString, OsStr};
 use std::fs::{self, File};
 use std::io::Write;
 use std::os::unix::prelude::OsStrExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 // A conservative overapproximation of `PATH_MAX` on all platforms.
 const PATH_MAX: usize = 4096;
 
-fn allowance_profile(path: &PathBuf) -> Result<Profile,()> {
+fn allowance_profile(path: &Path) -> Result<Profile,Error> {
     Profile::new(vec![
-        Operation::FileReadMetadata(PathPattern::Literal(path.clone())),
+        Operation::FileReadMetadata(PathPattern::Literal(path.to_path_buf())),
     ])
 }
 
-fn prohibition_profile() -> Result<Profile,()> {
+fn prohibition_profile() -> Result<Profile,Error> {
     Profile::new(vec![
         Operation::FileReadMetadata(PathPattern::Subpath(PathBuf::from("/bogus")))
     ])
@@ -35,7 +36,8 @@ fn prohibition_profile() -> Result<Profile,()> {
 fn allowance_test() {
     let path = PathBuf::from(env::var("GAOL_TEMP_FILE").unwrap());
     if ChildSandbox::new(allowance_profile(&path).unwrap()).activate().is_ok() {
-        drop(fs::metadata(path).unwrap())
+        fs::metadata(&path).unwrap();
+        assert!(File::open(&path).is_err())
     }
 }
 
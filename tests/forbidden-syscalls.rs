@@ -14,6 +14,12 @@ use gaol::platform::linux::seccomp::ALLOWED_SYSCALLS;
 
 const MAX_SYSCALL: u32 = 400;
 
+/// Syscalls that the kernel never passes through `seccomp`: `uretprobe` and `uprobe` on x86-64.
+#[cfg(all(target_os="linux", target_arch="x86_64"))]
+static UNFILTERED_SYSCALLS: [u32; 2] = [335, 336];
+#[cfg(all(target_os="linux", not(target_arch="x86_64")))]
+static UNFILTERED_SYSCALLS: [u32; 0] = [];
+
 fn profile() -> Profile {
     Profile::new(Vec::new()).unwrap()
 }
@@ -33,7 +39,8 @@ pub fn main() {
     }
 
     for syscall in 0..MAX_SYSCALL {
-        if ALLOWED_SYSCALLS.iter().any(|number| *number == syscall) {
+        if ALLOWED_SYSCALLS.iter().any(|allowed| allowed.number() == Some(syscall)) ||
+                UNFILTERED_SYSCALLS.contains(&syscall) {
             continue
         }
         let arg = format!("{}", syscall);
@@ -0,0 +1,92 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+
+use gaol::profile::{AddressPattern, Operation, OperationSupport, OperationSupportLevel};
+use gaol::profile::{PathPattern, Profile};
+use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process;
+
+fn socket_operation(directory: &Path) -> Operation {
+    Operation::NetworkOutbound(AddressPattern::LocalSocket(directory.join("allowed")))
+}
+
+// The supervisor connects to the socket from outside of the jail, so the jail doesn't need to
+// contain it.
+fn allowance_profile(directory: &Path) -> Profile {
+    Profile::new(vec![socket_operation(directory)]).unwrap()
+}
+
+// Both sockets are visible inside the sandbox, so only the supervisor can tell them apart.
+fn prohibition_profile(directory: &Path) -> Profile {
+    Profile::new(vec![
+        Operation::FileReadAll(PathPattern::Subpath(directory.to_path_buf())),
+        socket_operation(directory),
+    ]).unwrap()
+}
+
+fn allowance_test() {
+    let directory = PathBuf::from(env::var("GAOL_TEMP_DIRECTORY").unwrap());
+    ChildSandbox::new(allowance_profile(&directory)).activate().unwrap();
+    let mut stream = UnixStream::connect(directory.join("allowed")).unwrap();
+    stream.write_all(b"ping").unwrap()
+}
+
+fn prohibition_test() {
+    let directory = PathBuf::from(env::var("GAOL_TEMP_DIRECTORY").unwrap());
+    ChildSandbox::new(prohibition_profile(&directory)).activate().unwrap();
+    drop(UnixStream::connect(directory.join("forbidden")).unwrap())
+}
+
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "allowance_test" => return allowance_test(),
+        Some(ref arg) if arg == "prohibition_test" => return prohibition_test(),
+        _ => {}
+    }
+
+    let directory = fs::canonicalize(env::temp_dir()).unwrap()
+                                                      .join(format!("gaoltest.sockets.{}",
+                                                                    process::id()));
+
+    // Supervision depends on the kernel, so only test it where it is supported.
+    if socket_operation(&directory).support() != OperationSupportLevel::CanBeAllowed {
+        return
+    }
+
+    fs::create_dir(&directory).unwrap();
+    let allowed = UnixListener::bind(directory.join("allowed")).unwrap();
+    let _forbidden = UnixListener::bind(directory.join("forbidden")).unwrap();
+
+    let mut command = Command::me().unwrap();
+    command.arg("allowance_test").env("GAOL_TEMP_DIRECTORY", &directory);
+    let allowance_status = Sandbox::new(allowance_profile(&directory)).start(&mut command)
+                                                                      .unwrap()
+                                                                      .wait()
+                                                                      .unwrap();
+    // The connection that the supervisor made on the process's behalf is the process's own.
+    let mut message = String::new();
+    allowed.accept().unwrap().0.read_to_string(&mut message).unwrap();
+    assert_eq!(message, "ping");
+    // Starting the process leaves the command's environment as it was.
+    assert_eq!(command.env.len(), 1);
+
+    let prohibition_status =
+        Sandbox::new(prohibition_profile(&directory)).start(Command::me().unwrap()
+                                                                         .arg("prohibition_test")
+                                                                         .env("GAOL_TEMP_DIRECTORY",
+                                                                              &directory))
+                                                     .unwrap()
+                                                     .wait()
+                                                     .unwrap();
+
+    fs::remove_dir_all(&directory).unwrap();
+    assert!(allowance_status.success());
+    assert!(!prohibition_status.success());
+}
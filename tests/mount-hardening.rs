@@ -0,0 +1,82 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+extern crate libc;
+
+use gaol::profile::{Operation, PathPattern, Profile};
+use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
+use std::env;
+use std::ffi::CString;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::mem;
+use std::os::unix::prelude::{OsStrExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process;
+
+fn profile(directory: &Path) -> Profile {
+    Profile::new(vec![
+        Operation::FileReadAll(PathPattern::Subpath(directory.to_path_buf())),
+        Operation::FileWrite(PathPattern::Literal(directory.join("writable"))),
+    ]).unwrap()
+}
+
+fn mount_flags(path: &Path) -> libc::c_ulong {
+    let path = CString::new(path.as_os_str().as_bytes()).unwrap();
+    unsafe {
+        let mut stat: libc::statvfs = mem::zeroed();
+        assert!(libc::statvfs(path.as_ptr(), &mut stat) == 0);
+        stat.f_flag
+    }
+}
+
+#[cfg(target_os="linux")]
+fn hardening_test() {
+    let directory = PathBuf::from(env::var("GAOL_TEMP_DIRECTORY").unwrap());
+    ChildSandbox::new(profile(&directory)).activate().unwrap();
+
+    // Writes are allowed in general, but not to paths that are only readable.
+    OpenOptions::new().write(true).open(directory.join("writable")).unwrap()
+                      .write_all(b"written\n").unwrap();
+    let error = OpenOptions::new().write(true).open(directory.join("setuid")).unwrap_err();
+    assert_eq!(error.raw_os_error(), Some(libc::EROFS));
+
+    // Neither setuid binaries nor device nodes work in the readable paths.
+    let flags = mount_flags(&directory);
+    assert!(flags & libc::ST_RDONLY != 0);
+    assert!(flags & libc::ST_NOSUID != 0);
+    assert!(flags & libc::ST_NODEV != 0);
+    assert!(fs::metadata(directory.join("setuid")).unwrap().permissions().mode() & 0o4000 != 0);
+}
+
+#[cfg(target_os="linux")]
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "hardening_test" => return hardening_test(),
+        _ => {}
+    }
+
+    let directory = fs::canonicalize(env::temp_dir()).unwrap()
+                                                      .join(format!("gaoltest.mounts.{}",
+                                                                    process::id()));
+    fs::create_dir(&directory).unwrap();
+    File::create(directory.join("writable")).unwrap();
+    File::create(directory.join("setuid")).unwrap().write_all(b"#!/bin/sh\n").unwrap();
+    fs::set_permissions(directory.join("setuid"), fs::Permissions::from_mode(0o4755)).unwrap();
+
+    let status = Sandbox::new(profile(&directory)).start(Command::me().unwrap()
+                                                                     .arg("hardening_test")
+                                                                     .env("GAOL_TEMP_DIRECTORY",
+                                                                          &directory))
+                                                  .unwrap()
+                                                  .wait()
+                                                  .unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+    assert!(status.success());
+}
+
+#[cfg(not(target_os="linux"))]
+pub fn main() {
+    // Mount flags only apply to the Linux sandbox.
+}
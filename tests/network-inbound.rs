@@ -0,0 +1,65 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+
+use gaol::profile::{AddressPattern, Operation, Profile};
+use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
+use std::env;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+static ADDRESS: &str = "127.0.0.1:7358";
+
+fn allowance_profile() -> Profile {
+    Profile::new(vec![Operation::NetworkInbound(AddressPattern::All)]).unwrap()
+}
+
+fn prohibition_profile() -> Profile {
+    Profile::new(Vec::new()).unwrap()
+}
+
+fn accept_one_connection() {
+    let listener = TcpListener::bind(ADDRESS).unwrap();
+    drop(listener.accept().unwrap())
+}
+
+fn allowance_test() {
+    ChildSandbox::new(allowance_profile()).activate().unwrap();
+    accept_one_connection()
+}
+
+// Only binds, so that the process exits rather than waiting for a connection if the sandbox lets
+// it listen.
+fn prohibition_test() {
+    ChildSandbox::new(prohibition_profile()).activate().unwrap();
+    drop(TcpListener::bind(ADDRESS).unwrap())
+}
+
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "allowance_test" => return allowance_test(),
+        Some(ref arg) if arg == "prohibition_test" => return prohibition_test(),
+        _ => {}
+    }
+
+    let allowance_process =
+        Sandbox::new(allowance_profile()).start(Command::me().unwrap().arg("allowance_test"))
+                                         .unwrap();
+    // Keep trying until the child is listening.
+    for _ in 0..100 {
+        if TcpStream::connect(ADDRESS).is_ok() {
+            break
+        }
+        thread::sleep(Duration::from_millis(50))
+    }
+    assert!(allowance_process.wait().unwrap().success());
+
+    let prohibition_status =
+        Sandbox::new(prohibition_profile()).start(Command::me().unwrap().arg("prohibition_test"))
+                                           .unwrap()
+                                           .wait()
+                                           .unwrap();
+    assert!(!prohibition_status.success());
+}
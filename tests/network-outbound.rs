@@ -3,12 +3,13 @@
 
 extern crate gaol;
 
-use gaol::profile::{AddressPattern, Operation, Profile};
+use gaol::profile::{AddressPattern, Operation, OperationSupport, OperationSupportLevel, Profile};
 use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
 use std::env;
 use std::net::{TcpListener, TcpStream};
 
-static ADDRESS: &'static str = "127.0.0.1:7357";
+static ADDRESS: &str = "127.0.0.1:7357";
+static OTHER_ADDRESS: &str = "127.0.0.1:7358";
 
 fn allowance_profile() -> Profile {
     Profile::new(vec![Operation::NetworkOutbound(AddressPattern::All)]).unwrap()
@@ -18,6 +19,14 @@ fn prohibition_profile() -> Profile {
     Profile::new(Vec::new()).unwrap()
 }
 
+fn port_operation() -> Operation {
+    Operation::NetworkOutbound(AddressPattern::Tcp(7357))
+}
+
+fn port_profile() -> Profile {
+    Profile::new(vec![port_operation()]).unwrap()
+}
+
 fn allowance_test() {
     ChildSandbox::new(allowance_profile()).activate().unwrap();
     drop(TcpStream::connect(ADDRESS).unwrap())
@@ -28,14 +37,27 @@ fn prohibition_test() {
     drop(TcpStream::connect(ADDRESS).unwrap())
 }
 
+fn port_allowance_test() {
+    ChildSandbox::new(port_profile()).activate().unwrap();
+    drop(TcpStream::connect(ADDRESS).unwrap())
+}
+
+fn port_prohibition_test() {
+    ChildSandbox::new(port_profile()).activate().unwrap();
+    drop(TcpStream::connect(OTHER_ADDRESS).unwrap())
+}
+
 pub fn main() {
     match env::args().skip(1).next() {
         Some(ref arg) if arg == "allowance_test" => return allowance_test(),
         Some(ref arg) if arg == "prohibition_test" => return prohibition_test(),
+        Some(ref arg) if arg == "port_allowance_test" => return port_allowance_test(),
+        Some(ref arg) if arg == "port_prohibition_test" => return port_prohibition_test(),
         _ => {}
     }
 
     let listener = TcpListener::bind(ADDRESS).unwrap();
+    let other_listener = TcpListener::bind(OTHER_ADDRESS).unwrap();
     let _acceptor = listener.incoming();
 
     let allowance_status =
@@ -51,5 +73,24 @@ pub fn main() {
                                            .wait()
                                            .unwrap();
     assert!(!prohibition_status.success());
-}
 
+    // Per-port rules depend on the kernel, so only test them where they are supported.
+    if port_operation().support() != OperationSupportLevel::CanBeAllowed {
+        return
+    }
+
+    let port_allowance_status =
+        Sandbox::new(port_profile()).start(Command::me().unwrap().arg("port_allowance_test"))
+                                    .unwrap()
+                                    .wait()
+                                    .unwrap();
+    assert!(port_allowance_status.success());
+
+    let port_prohibition_status =
+        Sandbox::new(port_profile()).start(Command::me().unwrap().arg("port_prohibition_test"))
+                                    .unwrap()
+                                    .wait()
+                                    .unwrap();
+    assert!(!port_prohibition_status.success());
+    drop(other_listener)
+}
@@ -0,0 +1,76 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+
+use gaol::profile::{AddressPattern, Operation, PathPattern, PortRange, Profile};
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
+
+fn subpath(path: &str) -> PathPattern {
+    PathPattern::Subpath(PathBuf::from(path))
+}
+
+fn base_profile() -> Profile {
+    Profile::new(vec![
+        Operation::FileReadAll(subpath("/usr")),
+        Operation::FileWrite(subpath("/tmp")),
+    ]).unwrap()
+}
+
+fn component_profile() -> Profile {
+    Profile::new(vec![
+        Operation::FileReadAll(subpath("/usr")),
+        Operation::FileReadAll(subpath("/etc")),
+        Operation::FileWrite(subpath("/tmp/component")),
+    ]).unwrap()
+}
+
+fn merge_test() {
+    let merged = base_profile().merge(&component_profile()).unwrap();
+    assert_eq!(merged.allowed_operations(), &[
+        Operation::FileReadAll(subpath("/usr")),
+        Operation::FileWrite(subpath("/tmp")),
+        Operation::FileReadAll(subpath("/etc")),
+        Operation::FileWrite(subpath("/tmp/component")),
+    ]);
+}
+
+fn extend_test() {
+    let mut profile = base_profile();
+    profile.extend(vec![
+        Operation::FileWrite(subpath("/tmp")),
+        Operation::SignalSelf,
+    ]).unwrap();
+    assert_eq!(profile.allowed_operations(), &[
+        Operation::FileReadAll(subpath("/usr")),
+        Operation::FileWrite(subpath("/tmp")),
+        Operation::SignalSelf,
+    ]);
+}
+
+fn intersect_test() {
+    let intersection = base_profile().intersect(&component_profile()).unwrap();
+    assert_eq!(intersection.allowed_operations(), &[
+        Operation::FileReadAll(subpath("/usr")),
+        Operation::FileWrite(subpath("/tmp/component")),
+    ]);
+}
+
+fn address_intersection_test() {
+    let internal = AddressPattern::Ipv4Network(Ipv4Addr::new(10, 0, 0, 0), 8, None);
+    let https = AddressPattern::Ipv4Network(Ipv4Addr::new(10, 1, 0, 0),
+                                            16,
+                                            Some(PortRange::single(443)));
+    assert_eq!(internal.intersection(&https), Some(https.clone()));
+    assert_eq!(AddressPattern::All.intersection(&internal), Some(internal.clone()));
+    let elsewhere = AddressPattern::Ipv4Network(Ipv4Addr::new(192, 168, 0, 0), 16, None);
+    assert_eq!(internal.intersection(&elsewhere), None);
+}
+
+pub fn main() {
+    merge_test();
+    extend_test();
+    intersect_test();
+    address_intersection_test();
+}
@@ -0,0 +1,103 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+
+use gaol::loader::{self, Format, Variables};
+use gaol::profile::{EnvironmentVariable, Operation, PathPattern, Profile};
+use std::path::PathBuf;
+
+static TOML_PROFILE: &str = r#"
+operations = [
+    { file_read_all = { subpath = "$PREFIX/share" } },
+    { file_write = { literal = "${PREFIX}/log" } },
+    "signal_self",
+]
+environment = [
+    { name = "RUST_LOG" },
+    { name = "APP_PREFIX", value = "$PREFIX" },
+]
+
+[limits]
+cpu_seconds = 10
+open_files = 64
+"#;
+
+static JSON_PROFILE: &str = r#"
+{
+    "operations": [
+        { "file_read_all": { "subpath": "/usr" } },
+        { "file_read_all": { "literal": "$UNDEFINED/secret" } }
+    ]
+}
+"#;
+
+fn variables() -> Variables {
+    let mut variables = Variables::new();
+    variables.set("PREFIX", "/opt/app");
+    variables
+}
+
+fn load_test() {
+    let profile = loader::from_str(TOML_PROFILE, Format::Toml, &variables()).unwrap();
+    match profile.allowed_operations() {
+        [Operation::FileReadAll(PathPattern::Subpath(ref share)),
+         Operation::FileWrite(PathPattern::Literal(ref log)),
+         Operation::SignalSelf] => {
+            assert_eq!(*share, PathBuf::from("/opt/app/share"));
+            assert_eq!(*log, PathBuf::from("/opt/app/log"));
+        }
+        operations => panic!("unexpected operations: {:?}", operations),
+    }
+    assert_eq!(profile.resource_limits().cpu_seconds, Some(10));
+    assert_eq!(profile.resource_limits().open_files, Some(64));
+    assert_eq!(profile.resource_limits().address_space_bytes, None);
+    assert_eq!(profile.environment(),
+               &[EnvironmentVariable::inherit("RUST_LOG"),
+                 EnvironmentVariable::fixed("APP_PREFIX", "/opt/app")][..]);
+
+    for &format in &[Format::Toml, Format::Json] {
+        let text = loader::to_string(&profile, format).unwrap();
+        let reloaded = loader::from_str(&text, format, &variables()).unwrap();
+        assert_eq!(format!("{:?}", reloaded), format!("{:?}", profile));
+    }
+}
+
+/// A literal `$` survives being saved and loaded again.
+fn dollar_test() {
+    let operations = vec![Operation::FileReadAll(PathPattern::Literal(PathBuf::from("/a$b")))];
+    let mut profile = Profile::new(operations).unwrap();
+    profile.set_environment(vec![EnvironmentVariable::fixed("PRICE", "$5")]);
+    for &format in &[Format::Toml, Format::Json] {
+        let text = loader::to_string(&profile, format).unwrap();
+        let reloaded = loader::from_str(&text, format, &variables()).unwrap();
+        assert_eq!(format!("{:?}", reloaded), format!("{:?}", profile));
+    }
+}
+
+fn error_test() {
+    let error = loader::from_str(JSON_PROFILE, Format::Json, &variables()).unwrap_err();
+    assert_eq!(error.entry, Some(1));
+    assert!(error.message.contains("UNDEFINED"));
+
+    let text = r#"
+operations = [
+    { file_read_all = { subpath = "/usr/lib" } },
+    "signal_self",
+    { file_read_metadata = { subpath = "/usr" } },
+]
+"#;
+    let error = loader::from_str(text, Format::Toml, &variables()).unwrap_err();
+    assert_eq!(error.entry, Some(2));
+
+    let text = "operations = []\n[violations]\ndefault_action = { errno = 4096 }\n";
+    let error = loader::from_str(text, Format::Toml, &variables()).unwrap_err();
+    assert_eq!(error.entry, None);
+    assert!(error.message.contains("Errno(4096)"));
+}
+
+pub fn main() {
+    load_test();
+    dollar_test();
+    error_test();
+}
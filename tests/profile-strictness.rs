@@ -0,0 +1,90 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+
+use gaol::profile::{AddressPattern, Operation, OperationSupport, OperationSupportLevel};
+use gaol::profile::{PathPattern, Profile, Strictness};
+use std::path::PathBuf;
+
+fn operations() -> Vec<Operation> {
+    vec![
+        Operation::FileReadAll(PathPattern::Subpath(PathBuf::from("/usr"))),
+        Operation::FileReadMetadata(PathPattern::Subpath(PathBuf::from("/etc"))),
+        Operation::FileCreateAndUnlink(PathPattern::Literal(PathBuf::from("/tmp/app/lock"))),
+        Operation::NetworkOutbound(AddressPattern::Tcp(443)),
+        Operation::SystemInfoRead,
+    ]
+}
+
+fn precisely_allowable(operation: &Operation) -> bool {
+    match operation.support() {
+        OperationSupportLevel::NeverAllowed | OperationSupportLevel::CanBeAllowed => true,
+        OperationSupportLevel::CannotBeAllowedPrecisely |
+        OperationSupportLevel::AlwaysAllowed => false,
+    }
+}
+
+fn strict_test() {
+    let unsupported = operations().into_iter().any(|operation| !precisely_allowable(&operation));
+    assert_eq!(Profile::with_strictness(operations(), Strictness::Strict).is_err(), unsupported);
+}
+
+fn drop_unsupported_test() {
+    let (profile, downgrades) =
+        Profile::with_strictness(operations(), Strictness::DropUnsupported).unwrap();
+    let kept: Vec<Operation> =
+        operations().into_iter().filter(precisely_allowable).collect();
+    assert_eq!(profile.allowed_operations(), &kept[..]);
+    for downgrade in downgrades.iter() {
+        assert!(!precisely_allowable(&downgrade.requested));
+        assert_eq!(downgrade.allowed, None);
+        assert_eq!(downgrade.support, downgrade.requested.support());
+    }
+    assert_eq!(kept.len() + downgrades.len(), operations().len());
+}
+
+fn widen_to_coarser_test() {
+    let (profile, downgrades) =
+        Profile::with_strictness(operations(), Strictness::WidenToCoarser).unwrap();
+    for downgrade in downgrades.iter() {
+        assert!(!precisely_allowable(&downgrade.requested));
+        if let Some(ref allowed) = downgrade.allowed {
+            assert!(precisely_allowable(allowed));
+            assert!(profile.allowed_operations().contains(allowed));
+        }
+    }
+
+    if cfg!(any(target_os="android", target_os="linux")) {
+        // Metadata reads and TCP ports can be allowed precisely if the kernel's Landlock
+        // supports them.
+        let metadata = operations()[1].clone();
+        let metadata_supported = precisely_allowable(&metadata);
+        let tcp = operations()[3].clone();
+        let tcp_supported = precisely_allowable(&tcp);
+        assert_eq!(profile.allowed_operations(), &[
+            Operation::FileReadAll(PathPattern::Subpath(PathBuf::from("/usr"))),
+            if metadata_supported {
+                metadata
+            } else {
+                Operation::FileReadAll(PathPattern::Subpath(PathBuf::from("/etc")))
+            },
+            Operation::FileCreateAndUnlink(PathPattern::Subpath(PathBuf::from("/tmp/app"))),
+            if tcp_supported {
+                tcp
+            } else {
+                Operation::NetworkOutbound(AddressPattern::All)
+            },
+            Operation::SystemInfoRead,
+        ]);
+        let expected_downgrades =
+            1 + if metadata_supported { 0 } else { 1 } + if tcp_supported { 0 } else { 1 };
+        assert_eq!(downgrades.len(), expected_downgrades);
+    }
+}
+
+pub fn main() {
+    strict_test();
+    drop_unsupported_test();
+    widen_to_coarser_test();
+}
@@ -0,0 +1,46 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+
+use gaol::platform::process::ExitStatus;
+use gaol::profile::{Profile, ResourceLimit, ResourceLimits};
+use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
+use std::env;
+use std::hint;
+
+fn profile() -> Profile {
+    let mut profile = Profile::new(Vec::new()).unwrap();
+    profile.set_resource_limits(ResourceLimits {
+        cpu_seconds: Some(1),
+        ..ResourceLimits::default()
+    });
+    profile
+}
+
+pub fn prohibition_test() -> ! {
+    ChildSandbox::new(profile()).activate().unwrap();
+    let mut counter = 0u64;
+    loop {
+        counter = hint::black_box(counter.wrapping_add(1));
+    }
+}
+
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "prohibition_test" => prohibition_test(),
+        _ => {}
+    }
+
+    let status = Sandbox::new(profile()).start(Command::me().unwrap()
+                                                             .arg("prohibition_test"))
+                                         .unwrap()
+                                         .wait()
+                                         .unwrap();
+    match status {
+        ExitStatus::LimitExceeded(ResourceLimit::CpuTime) => {}
+        ExitStatus::Code(code) => panic!("exited with {}", code),
+        ExitStatus::Signal(signal) => panic!("signal {}", signal),
+        _ => panic!("the CPU time limit wasn't enforced"),
+    }
+}
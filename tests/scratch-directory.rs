@@ -0,0 +1,79 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+
+use gaol::profile::{Operation, Profile};
+use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const MAX_BYTES: u64 = 1024 * 1024;
+
+fn profile(path: &Path) -> Profile {
+    Profile::new(vec![
+        Operation::ScratchDirectory {
+            path: path.to_path_buf(),
+            max_bytes: MAX_BYTES,
+        },
+    ]).unwrap()
+}
+
+fn allowance_test() {
+    let path = PathBuf::from(env::var("GAOL_SCRATCH_DIRECTORY").unwrap());
+    ChildSandbox::new(profile(&path)).activate().unwrap();
+    assert!(fs::read_dir(&path).unwrap().next().is_none());
+    fs::create_dir(path.join("nested")).unwrap();
+    File::create(path.join("nested/file")).unwrap().write_all(b"scratch\n").unwrap();
+    fs::remove_file(path.join("nested/file")).unwrap();
+    fs::remove_dir(path.join("nested")).unwrap();
+}
+
+#[cfg(target_os="linux")]
+fn quota_test() {
+    let path = PathBuf::from(env::var("GAOL_SCRATCH_DIRECTORY").unwrap());
+    ChildSandbox::new(profile(&path)).activate().unwrap();
+    let chunk = vec![0; 64 * 1024];
+    let mut file = File::create(path.join("big")).unwrap();
+    for _ in 0..(2 * MAX_BYTES as usize / chunk.len()) {
+        file.write_all(&chunk).unwrap()
+    }
+}
+
+fn prohibition_test() {
+    let path = PathBuf::from(env::var("GAOL_SCRATCH_DIRECTORY").unwrap());
+    ChildSandbox::new(profile(&path)).activate().unwrap();
+    File::create(path.parent().unwrap().join("outside")).unwrap().write_all(b"escaped\n").unwrap()
+}
+
+fn run(path: &PathBuf, test: &str) -> bool {
+    Sandbox::new(profile(path)).start(Command::me().unwrap()
+                                                   .arg(test)
+                                                   .env("GAOL_SCRATCH_DIRECTORY", path))
+                               .unwrap()
+                               .wait()
+                               .unwrap()
+                               .success()
+}
+
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "allowance_test" => return allowance_test(),
+        #[cfg(target_os="linux")]
+        Some(ref arg) if arg == "quota_test" => return quota_test(),
+        Some(ref arg) if arg == "prohibition_test" => return prohibition_test(),
+        _ => {}
+    }
+
+    let path = fs::canonicalize(env::temp_dir()).unwrap()
+                                                 .join(format!("gaoltest.scratch.{}",
+                                                               process::id()));
+    assert!(run(&path, "allowance_test"));
+    #[cfg(target_os="linux")]
+    assert!(!run(&path, "quota_test"));
+    assert!(!run(&path, "prohibition_test"));
+    assert!(!path.exists());
+}
@@ -0,0 +1,56 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+extern crate libc;
+
+use gaol::profile::{Operation, Profile};
+use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
+use std::env;
+
+fn allowance_profile() -> Profile {
+    Profile::new(vec![Operation::SignalSelf]).unwrap()
+}
+
+fn prohibition_profile() -> Profile {
+    Profile::new(Vec::new()).unwrap()
+}
+
+fn signal_self(pid: libc::pid_t) {
+    // Signal 0 performs all of the permission checks without actually delivering anything.
+    assert!(unsafe { libc::kill(pid, 0) } == 0)
+}
+
+fn allowance_test() {
+    let pid = unsafe { libc::getpid() };
+    ChildSandbox::new(allowance_profile()).activate().unwrap();
+    signal_self(pid)
+}
+
+fn prohibition_test() {
+    let pid = unsafe { libc::getpid() };
+    ChildSandbox::new(prohibition_profile()).activate().unwrap();
+    signal_self(pid)
+}
+
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "allowance_test" => return allowance_test(),
+        Some(ref arg) if arg == "prohibition_test" => return prohibition_test(),
+        _ => {}
+    }
+
+    let allowance_status =
+        Sandbox::new(allowance_profile()).start(Command::me().unwrap().arg("allowance_test"))
+                                         .unwrap()
+                                         .wait()
+                                         .unwrap();
+    assert!(allowance_status.success());
+
+    let prohibition_status =
+        Sandbox::new(prohibition_profile()).start(Command::me().unwrap().arg("prohibition_test"))
+                                           .unwrap()
+                                           .wait()
+                                           .unwrap();
+    assert!(!prohibition_status.success());
+}
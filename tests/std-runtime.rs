@@ -0,0 +1,108 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+
+use gaol::profile::{Operation, PathPattern, Profile};
+use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Directories that the dynamic linker and system libraries live in.
+static LIBRARY_PATHS: [&str; 5] = [
+    "/lib",
+    "/lib64",
+    "/usr/lib",
+    "/usr/lib64",
+    "/System/Library",
+];
+
+fn empty_profile() -> Profile {
+    Profile::new(Vec::new()).unwrap()
+}
+
+fn exec_profile(path: &Path) -> Profile {
+    let mut operations = vec![Operation::Exec(PathPattern::Literal(path.to_path_buf()))];
+    operations.extend(LIBRARY_PATHS.iter().map(|library_path| {
+        Operation::Exec(PathPattern::Subpath(PathBuf::from(library_path)))
+    }));
+    Profile::new(operations).unwrap()
+}
+
+// The parts of the standard library that nearly every program uses, none of which should need
+// anything from the profile.
+fn exercise_runtime() {
+    let counter = Arc::new(Mutex::new(0));
+    let (sender, receiver) = mpsc::channel();
+    let threads: Vec<_> = (0..4).map(|index| {
+        let (counter, sender) = (counter.clone(), sender.clone());
+        thread::spawn(move || {
+            thread::yield_now();
+            *counter.lock().unwrap() += 1;
+            sender.send(index).unwrap()
+        })
+    }).collect();
+    drop(sender);
+    for thread in threads.into_iter() {
+        thread.join().unwrap()
+    }
+    let mut indices: Vec<_> = receiver.iter().collect();
+    indices.sort();
+    assert_eq!(indices, vec![0, 1, 2, 3]);
+    assert_eq!(*counter.lock().unwrap(), 4);
+
+    let start = Instant::now();
+    thread::sleep(Duration::from_millis(10));
+    assert!(start.elapsed() >= Duration::from_millis(10));
+    assert!(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() > 0);
+    assert!(process::id() > 0);
+
+    let mut buffer = vec![0u8; 1024];
+    for size in [64 * 1024, 16 * 1024 * 1024].iter() {
+        buffer.resize(*size, 1);
+    }
+    println!("exercised the runtime with {} bytes", buffer.len());
+}
+
+fn in_process_test() {
+    ChildSandbox::new(empty_profile()).activate().unwrap();
+    exercise_runtime()
+}
+
+// A program that starts under the filter has to get through the dynamic linker and the C
+// library's startup code as well.
+fn exec_test() {
+    let path = env::current_exe().unwrap();
+    ChildSandbox::new(exec_profile(&path)).activate().unwrap();
+    let status = Command::new(&path).arg("exercise").spawn().unwrap().wait().unwrap();
+    assert!(status.success())
+}
+
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "in_process_test" => return in_process_test(),
+        Some(ref arg) if arg == "exec_test" => return exec_test(),
+        Some(ref arg) if arg == "exercise" => return exercise_runtime(),
+        _ => {}
+    }
+
+    let in_process_status =
+        Sandbox::new(empty_profile()).start(Command::me().unwrap().arg("in_process_test"))
+                                     .unwrap()
+                                     .wait()
+                                     .unwrap();
+    assert!(in_process_status.success());
+
+    let path = env::current_exe().unwrap();
+    let exec_status = Sandbox::new(exec_profile(&path)).start(Command::me().unwrap()
+                                                                           .arg("exec_test"))
+                                                       .unwrap()
+                                                       .wait()
+                                                       .unwrap();
+    assert!(exec_status.success());
+}
@@ -0,0 +1,110 @@
+// Any copyright is dedicated to the Public Domain.
+// http://creativecommons.org/publicdomain/zero/1.0/
+
+extern crate gaol;
+extern crate libc;
+
+#[cfg(target_os="linux")]
+use gaol::platform::linux::seccomp;
+#[cfg(target_os="linux")]
+use gaol::platform::process::ExitStatus;
+#[cfg(target_os="linux")]
+use gaol::profile::{Profile, ViolationAction, ViolationOverride, ViolationPolicy};
+#[cfg(target_os="linux")]
+use gaol::sandbox::{ChildSandbox, ChildSandboxMethods, Command, Sandbox, SandboxMethods};
+#[cfg(target_os="linux")]
+use std::env;
+#[cfg(target_os="linux")]
+use std::io;
+
+#[cfg(target_os="linux")]
+fn profile(policy: ViolationPolicy) -> Profile {
+    let mut profile = Profile::new(Vec::new()).unwrap();
+    profile.set_violation_policy(policy).unwrap();
+    profile
+}
+
+#[cfg(target_os="linux")]
+fn errno_profile() -> Profile {
+    profile(ViolationPolicy::with_default_action(ViolationAction::Errno(libc::EPERM as u16)))
+}
+
+#[cfg(target_os="linux")]
+fn trap_profile() -> Profile {
+    profile(ViolationPolicy::with_default_action(ViolationAction::Trap))
+}
+
+// Violations fail with `EPERM`, except for `getppid`, which kills the process.
+#[cfg(target_os="linux")]
+fn override_profile() -> Profile {
+    profile(ViolationPolicy {
+        default_action: ViolationAction::Errno(libc::EPERM as u16),
+        overrides: vec![
+            ViolationOverride {
+                syscall: libc::SYS_getppid as u32,
+                action: ViolationAction::KillProcess,
+            },
+        ],
+    })
+}
+
+/// Makes a system call that no profile allows, returning its result and `errno`.
+#[cfg(target_os="linux")]
+fn forbidden_syscall() -> (libc::c_long, Option<i32>) {
+    let result = unsafe {
+        libc::syscall(libc::SYS_getppid)
+    };
+    (result, io::Error::last_os_error().raw_os_error())
+}
+
+#[cfg(target_os="linux")]
+fn errno_test() {
+    ChildSandbox::new(errno_profile()).activate().unwrap();
+    assert_eq!(forbidden_syscall(), (-1, Some(libc::EPERM)));
+}
+
+#[cfg(target_os="linux")]
+fn trap_test() {
+    ChildSandbox::new(trap_profile()).activate().unwrap();
+    assert_eq!(seccomp::last_trapped_syscall(), None);
+    let (result, errno) = forbidden_syscall();
+    assert_eq!(seccomp::last_trapped_syscall(), Some(libc::SYS_getppid as u32));
+    if cfg!(any(target_arch="x86", target_arch="x86_64", target_arch="arm",
+                target_arch="aarch64", target_arch="riscv64")) {
+        assert_eq!((result, errno), (-1, Some(libc::ENOSYS)));
+    }
+}
+
+#[cfg(target_os="linux")]
+fn override_test() {
+    ChildSandbox::new(override_profile()).activate().unwrap();
+    forbidden_syscall();
+}
+
+#[cfg(target_os="linux")]
+fn run(profile: Profile, test: &str) -> ExitStatus {
+    Sandbox::new(profile).start(Command::me().unwrap().arg(test))
+                         .unwrap()
+                         .wait()
+                         .unwrap()
+}
+
+#[cfg(target_os="linux")]
+pub fn main() {
+    match env::args().nth(1) {
+        Some(ref arg) if arg == "errno_test" => return errno_test(),
+        Some(ref arg) if arg == "trap_test" => return trap_test(),
+        Some(ref arg) if arg == "override_test" => return override_test(),
+        _ => {}
+    }
+
+    assert!(run(errno_profile(), "errno_test").success());
+    assert!(run(trap_profile(), "trap_test").success());
+    match run(override_profile(), "override_test") {
+        ExitStatus::Signal(signal) => assert_eq!(signal, libc::SIGSYS),
+        _ => panic!("the process wasn't killed"),
+    }
+}
+
+#[cfg(not(target_os="linux"))]
+pub fn main() {}